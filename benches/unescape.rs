@@ -0,0 +1,56 @@
+// Copyright (C) 2026 Scott Lamb <slamb@slamb.org>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Compares [`ParamValue::to_unescaped`] on values with and without escapes.
+//!
+//! Run with `cargo bench --bench unescape`. This uses a simple timing loop
+//! rather than a benchmarking framework to avoid extra dependencies. Under
+//! `cargo test --all-targets`, each case runs once as a smoke test.
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use http_auth::ParamValue;
+
+fn bench(name: &str, iters: u32, mut f: impl FnMut()) {
+    let start = Instant::now();
+    for _ in 0..iters {
+        f();
+    }
+    let elapsed = start.elapsed();
+    println!(
+        "{:<24} {:>8.1} ns/iter",
+        name,
+        elapsed.as_nanos() as f64 / f64::from(iters)
+    );
+}
+
+fn main() {
+    let iters = if std::env::args().any(|a| a == "--bench") {
+        10_000_000
+    } else {
+        1
+    };
+    let plain =
+        ParamValue::try_from_escaped("7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v").unwrap();
+    let escaped =
+        ParamValue::try_from_escaped(r#"7ypf/xlj9XXw\"fDPEoM4URrv/xwf9\\4BcCAzFZH4GiTo0v"#)
+            .unwrap();
+    bench("to_unescaped/plain", iters, || {
+        black_box(black_box(&plain).to_unescaped());
+    });
+    bench("to_unescaped/escaped", iters, || {
+        black_box(black_box(&escaped).to_unescaped());
+    });
+    let mut out = String::with_capacity(64);
+    bench("append_unescaped/plain", iters, || {
+        out.clear();
+        black_box(&plain).append_unescaped(&mut out);
+        black_box(&out);
+    });
+    bench("append_unescaped/escaped", iters, || {
+        out.clear();
+        black_box(&escaped).append_unescaped(&mut out);
+        black_box(&out);
+    });
+}
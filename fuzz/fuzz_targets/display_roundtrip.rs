@@ -0,0 +1,33 @@
+// Copyright (C) 2026 Scott Lamb <slamb@slamb.org>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+// Parses challenges, formats them via `Display`, and parses the result again,
+// failing if the second parse fails or differs from the first. This checks
+// the formatter's quoting and escaping against the parser. Run via:
+//
+// ```console
+// $ cd .../http-auth/fuzz
+// $ RUST_LOG=http_auth=trace cargo +nightly fuzz run display_roundtrip
+
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &str| {
+    let _ = env_logger::builder().try_init();
+    let parsed = match http_auth::parse_challenges(data) {
+        Ok(p) => p,
+        Err(_) => return,
+    };
+    let formatted = parsed
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ");
+    let reparsed = http_auth::parse_challenges(&formatted).unwrap_or_else(|e| {
+        panic!(
+            "formatted {:#?} as {:?}, which failed to parse: {}",
+            parsed, formatted, e
+        )
+    });
+    assert_eq!(parsed, reparsed, "formatted as {:?}", formatted);
+});
@@ -15,16 +15,36 @@ use libfuzzer_sys::fuzz_target;
 fuzz_target!(|data: &str| {
     let _ = env_logger::builder().try_init();
     let hand_parsed = http_auth::parse_challenges(data);
-    let nom_parsed = http_auth_fuzz::challenges(data);
+    let bytes_parsed = http_auth::parser::ChallengeParser::new_bytes(data.as_bytes())
+        .collect::<Result<Vec<_>, _>>();
+    assert_eq!(hand_parsed, bytes_parsed);
+    let nom_parsed = http_auth_fuzz::challenges_with_token68(data);
     match (hand_parsed, nom_parsed) {
         (Ok(hand_challenges), Ok((_, nom_challenges))) => {
+            // `http_auth` rejects `token68` challenges, so all should have params.
+            let nom_challenges: Vec<_> = nom_challenges
+                .into_iter()
+                .map(|c| match c {
+                    http_auth_fuzz::Challenge::Params(c) => c,
+                    http_auth_fuzz::Challenge::Token68 { scheme, token68 } => panic!(
+                        "hand parsing succeeded with {:#?}; nom parsing found token68 {:?} for {:?}",
+                        hand_challenges, token68, scheme
+                    ),
+                })
+                .collect();
             assert_eq!(hand_challenges, nom_challenges)
         }
         (Err(hand_e), Ok((_, nom_challenges))) => {
-            panic!(
-                "hand parsing failed with {}; nom parsing succeeded with {:#?}",
-                hand_e, nom_challenges
-            );
+            // Expected iff nom found a `token68` challenge.
+            if !nom_challenges
+                .iter()
+                .any(|c| matches!(c, http_auth_fuzz::Challenge::Token68 { .. }))
+            {
+                panic!(
+                    "hand parsing failed with {}; nom parsing succeeded with {:#?}",
+                    hand_e, nom_challenges
+                );
+            }
         }
         (Ok(hand_challenges), Err(nom_e)) => {
             panic!(
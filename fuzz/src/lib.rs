@@ -13,9 +13,9 @@ use log::trace;
 use nom::branch::alt;
 use nom::bytes::complete::is_a;
 use nom::character::complete::{char, satisfy};
-use nom::combinator::{all_consuming, consumed, map, opt, value};
+use nom::combinator::{all_consuming, consumed, eof, map, map_opt, opt, peek, recognize, value};
 use nom::multi::{fold_many0, many0_count, many1, many1_count, separated_list0, separated_list1};
-use nom::sequence::{delimited, pair, preceded, separated_pair, tuple};
+use nom::sequence::{delimited, pair, preceded, separated_pair, terminated, tuple};
 
 use http_auth::{ChallengeRef, ParamValue};
 
@@ -99,6 +99,20 @@ fn auth_param(input: &str) -> nom::IResult<&str, (&str, ParamValue)> {
     )(input)
 }
 
+/// Parses `token68` as in [RFC 7235 section
+/// 2.1](https://datatracker.ietf.org/doc/html/rfc7235#section-2.1).
+///
+/// ```text
+///   token68 = 1*( ALPHA / DIGIT / "-" / "." / "_" / "~" / "+" / "/" ) *"="
+/// ```
+pub fn token68(input: &str) -> nom::IResult<&str, &str> {
+    trace!("token68 attempt on {:?}", input);
+    recognize(pair(
+        is_a("-._~+/0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ"),
+        many0_count(char('=')),
+    ))(input)
+}
+
 /// Parses `1#element` as defined in
 /// [RFC 7230 section 7](https://datatracker.ietf.org/doc/html/rfc7230#section-7).
 ///
@@ -170,18 +184,45 @@ where
 ///
 /// Although in practice this is ambiguous when placed into a `1#challenge`,
 /// which we resolve by using `list0_relaxed_inner` rather than `list0_relaxed`.
-fn challenge(input: &str) -> nom::IResult<&str, ChallengeRef> {
+///
+/// `token68` and `#auth-param` overlap only in that both may be empty, so the
+/// `token68` alternative is tried first and accepted only if it ends the
+/// element.
+fn challenge(input: &str) -> nom::IResult<&str, Challenge<'_>> {
     trace!("challenge attempt on {:?}", input);
-    map(
-        tuple((
-            token,
-            opt(preceded(char(' '), list0_relaxed_inner(auth_param))),
-        )),
-        |(scheme, opt_params)| ChallengeRef {
-            scheme,
-            params: opt_params.unwrap_or_default(),
-        },
-    )(input)
+    alt((
+        map(
+            separated_pair(
+                token,
+                char(' '),
+                terminated(token68, peek(alt((eof, recognize(pair(ows, char(','))))))),
+            ),
+            |(scheme, token68)| Challenge::Token68 { scheme, token68 },
+        ),
+        map(
+            tuple((
+                token,
+                opt(preceded(char(' '), list0_relaxed_inner(auth_param))),
+            )),
+            |(scheme, opt_params)| {
+                Challenge::Params(ChallengeRef {
+                    scheme,
+                    params: opt_params.unwrap_or_default(),
+                })
+            },
+        ),
+    ))(input)
+}
+
+/// A challenge as parsed by [`challenges_with_token68`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Challenge<'i> {
+    /// A challenge with an `#auth-param` list, possibly empty.
+    Params(ChallengeRef<'i>),
+
+    /// A challenge with a `token68`, which `http_auth::ChallengeRef` can't
+    /// represent, so `http_auth` rejects it.
+    Token68 { scheme: &'i str, token68: &'i str },
 }
 
 /// Appends the challenges described by `value` into `challenges`.
@@ -195,10 +236,24 @@ fn challenge(input: &str) -> nom::IResult<&str, ChallengeRef> {
 ///   WWW-Authenticate = *( "," OWS ) challenge *( OWS "," [ OWS challenge
 ///    ] )
 /// ```
-pub fn challenges(input: &str) -> nom::IResult<&str, Vec<ChallengeRef>> {
+pub fn challenges_with_token68(input: &str) -> nom::IResult<&str, Vec<Challenge<'_>>> {
     all_consuming(list1_relaxed(challenge))(input)
 }
 
+/// Parses as in [`challenges_with_token68`], failing on any `token68`
+/// challenge as `http_auth` does.
+pub fn challenges(input: &str) -> nom::IResult<&str, Vec<ChallengeRef>> {
+    map_opt(challenges_with_token68, |challenges| {
+        challenges
+            .into_iter()
+            .map(|c| match c {
+                Challenge::Params(c) => Some(c),
+                Challenge::Token68 { .. } => None,
+            })
+            .collect()
+    })(input)
+}
+
 #[cfg(test)]
 mod tests {
     use nom::bytes::complete::tag;
@@ -238,6 +293,124 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_bare_scheme() {
+        // `Negotiate` and `NTLM` challenges often have no params or token.
+        for input in ["Negotiate", "Negotiate, NTLM", "Negotiate, Basic realm=foo"] {
+            let (rest, nom_challenges) = challenges(input).unwrap();
+            assert_eq!(rest, "");
+            assert_eq!(nom_challenges[0], ChallengeRef::new("Negotiate"));
+            assert_eq!(
+                http_auth::parse_challenges(input).unwrap(),
+                nom_challenges,
+                "{:?}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_scheme_comma() {
+        // The comma separates list elements; it's never part of the challenge.
+        for input in [
+            "Basic,",
+            "Basic ,",
+            "Basic,,",
+            "Basic, Digest realm=foo",
+            "Basic , Digest realm=foo",
+        ] {
+            let (rest, nom_challenges) = challenges(input).unwrap();
+            assert_eq!(rest, "", "{:?}", input);
+            assert_eq!(nom_challenges[0], ChallengeRef::new("Basic"));
+            assert_eq!(
+                http_auth::parse_challenges(input).unwrap(),
+                nom_challenges,
+                "{:?}",
+                input
+            );
+        }
+
+        // Trailing whitespace isn't part of a field value, so the grammar
+        // doesn't allow it after the final comma.
+        challenges("Basic, ").unwrap_err();
+        http_auth::parse_challenges("Basic, ").unwrap_err();
+    }
+
+    #[test]
+    fn test_trailing_backslash() {
+        // The final quote is escaped, leaving the string unterminated.
+        for input in [
+            r#"Basic realm="foo\""#,
+            r#"Basic realm="\""#,
+            r#"Basic realm="foo\"#,
+            r#"Basic realm="foo\", Digest"#,
+            r#"Basic realm="foo\\\""#,
+        ] {
+            challenges(input).unwrap_err();
+            http_auth::parse_challenges(input).unwrap_err();
+        }
+
+        // An escaped backslash followed by the closing quote is fine.
+        let input = r#"Basic realm="foo\\""#;
+        let expected = vec![ChallengeRef {
+            scheme: "Basic",
+            params: vec![("realm", ParamValue::new(1, r#"foo\\"#))],
+        }];
+        assert_eq!(challenges(input), Ok(("", expected.clone())));
+        assert_eq!(http_auth::parse_challenges(input), Ok(expected));
+    }
+
+    #[test]
+    fn test_escaped_crlf() {
+        for input in [
+            "Basic realm=\"a\\\r\"",
+            "Basic realm=\"a\\\n\"",
+            "Basic realm=\"a\\\r\nSet-Cookie: b\"",
+        ] {
+            challenges(input).unwrap_err();
+            http_auth::parse_challenges(input).unwrap_err();
+        }
+    }
+
+    #[test]
+    fn test_token68() {
+        assert_eq!(token68("abc123=="), Ok(("", "abc123==")));
+        assert_eq!(token68("a/b+c, foo"), Ok((", foo", "a/b+c")));
+        assert_eq!(
+            token68("=="),
+            Err(Err::Error(Error::new("==", ErrorKind::IsA)))
+        );
+
+        // Until `ChallengeRef` can represent them, both parsers reject token68 challenges.
+        for (input, expected) in [
+            ("Negotiate abc123==", vec![("Negotiate", "abc123==")]),
+            ("Basic realm", vec![("Basic", "realm")]),
+            ("A b, c", vec![("A", "b")]),
+            ("A b== ,B", vec![("A", "b==")]),
+            ("A x=1, B y==, C z", vec![("B", "y=="), ("C", "z")]),
+        ] {
+            let (_, nom_challenges) = challenges_with_token68(input).unwrap();
+            let token68s: Vec<_> = nom_challenges
+                .iter()
+                .filter_map(|c| match c {
+                    Challenge::Token68 { scheme, token68 } => Some((*scheme, *token68)),
+                    Challenge::Params(_) => None,
+                })
+                .collect();
+            assert_eq!(token68s, expected, "{:?}", input);
+            challenges(input).unwrap_err();
+            http_auth::parse_challenges(input).unwrap_err();
+        }
+
+        // An auth-param which starts out looking like a token68 isn't one.
+        let (_, nom_challenges) = challenges_with_token68("A b= c, d =e").unwrap();
+        assert!(
+            matches!(&nom_challenges[..], [Challenge::Params(c)] if c.params.len() == 2),
+            "{:?}",
+            nom_challenges
+        );
+    }
+
     #[test]
     fn test_list1() {
         assert_eq!(
@@ -27,13 +27,82 @@ use crate::ChallengeRef;
 ///     http_auth::basic::encode_credentials("Aladdin", "open sesame"),
 ///     "Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ==",
 /// );
+/// ```
 pub fn encode_credentials(username: &str, password: &str) -> String {
-    use base64::Engine as _;
-    let user_pass = format!("{}:{}", username, password);
-    const PREFIX: &str = "Basic ";
-    let mut value = String::with_capacity(PREFIX.len() + base64_encoded_len(user_pass.len()));
-    value.push_str(PREFIX);
-    base64::engine::general_purpose::STANDARD.encode_string(&user_pass[..], &mut value);
+    encode_userpass(&format!("{}:{}", username, password))
+}
+
+/// Encodes the given credentials as the bare base64 `token68`, without the
+/// `Basic ` prefix of [`encode_credentials`].
+///
+/// This is for carriers other than an HTTP `Authorization` header, such as a
+/// protocol which takes the credential in a separate field.
+///
+/// ```rust
+/// assert_eq!(
+///     http_auth::basic::encode_credential("Aladdin", "open sesame"),
+///     "QWxhZGRpbjpvcGVuIHNlc2FtZQ==",
+/// );
+/// ```
+pub fn encode_credential(username: &str, password: &str) -> String {
+    encode_with_prefix(
+        &base64::engine::general_purpose::STANDARD,
+        "",
+        &format!("{}:{}", username, password),
+    )
+}
+
+/// Encodes already-joined `user-pass` credentials, as in [RFC 7617 section
+/// 2](https://datatracker.ietf.org/doc/html/rfc7617#section-2).
+///
+/// This is useful when credentials are stored as a single `user:password`
+/// string. The caller is responsible for `user_pass` being in the correct
+/// format: the first colon separates the user-id from the password, so the
+/// user-id must not contain one. The password may.
+///
+/// ```rust
+/// assert_eq!(
+///     http_auth::basic::encode_userpass("Aladdin:open sesame"),
+///     "Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ==",
+/// );
+/// ```
+pub fn encode_userpass(user_pass: &str) -> String {
+    encode_userpass_with_engine(&base64::engine::general_purpose::STANDARD, user_pass)
+}
+
+/// Encodes the given credentials with a non-standard base64 engine.
+///
+/// RFC 7617 requires the standard alphabet with padding, as used by
+/// [`encode_credentials`]. This is only for interoperability with
+/// nonconforming servers, such as some embedded devices. Note that the
+/// [`base64`] crate's version is part of this function's signature; it may
+/// change in a semver-compatible release of this crate.
+///
+/// ```rust
+/// let engine = base64::engine::general_purpose::STANDARD_NO_PAD;
+/// assert_eq!(
+///     http_auth::basic::encode_credentials_with_engine(&engine, "Aladdin", "open sesame"),
+///     "Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ",
+/// );
+/// ```
+pub fn encode_credentials_with_engine<E: base64::Engine>(
+    engine: &E,
+    username: &str,
+    password: &str,
+) -> String {
+    encode_userpass_with_engine(engine, &format!("{}:{}", username, password))
+}
+
+fn encode_userpass_with_engine<E: base64::Engine>(engine: &E, user_pass: &str) -> String {
+    encode_with_prefix(engine, "Basic ", user_pass)
+}
+
+/// Returns `prefix` followed by the base64 encoding of `user_pass`, in a
+/// single allocation.
+fn encode_with_prefix<E: base64::Engine>(engine: &E, prefix: &str, user_pass: &str) -> String {
+    let mut value = String::with_capacity(prefix.len() + base64_encoded_len(user_pass.len()));
+    value.push_str(prefix);
+    engine.encode_string(user_pass, &mut value);
     value
 }
 
@@ -42,6 +111,54 @@ fn base64_encoded_len(input_len: usize) -> usize {
     (input_len + 2) / 3 * 4
 }
 
+/// The default limit on header value length for [`decode_credentials`].
+pub const DEFAULT_MAX_CREDENTIALS_LEN: usize = 8192;
+
+/// Decodes a `Basic` `Authorization` or `Proxy-Authorization` header value
+/// into a `(username, password)` pair, for servers.
+///
+/// Values longer than [`DEFAULT_MAX_CREDENTIALS_LEN`] bytes are rejected
+/// without decoding; see [`decode_credentials_with_limit`].
+///
+/// ```rust
+/// assert_eq!(
+///     http_auth::basic::decode_credentials("Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ==").unwrap(),
+///     ("Aladdin".to_owned(), "open sesame".to_owned()),
+/// );
+/// ```
+pub fn decode_credentials(value: &str) -> Result<(String, String), String> {
+    decode_credentials_with_limit(value, DEFAULT_MAX_CREDENTIALS_LEN)
+}
+
+/// Decodes as in [`decode_credentials`], with a custom length limit.
+///
+/// The limit is checked before decoding, so an oversized value costs no
+/// allocation.
+pub fn decode_credentials_with_limit(
+    value: &str,
+    max_len: usize,
+) -> Result<(String, String), String> {
+    if value.len() > max_len {
+        return Err(format!(
+            "credentials length {} exceeds limit of {}",
+            value.len(),
+            max_len
+        ));
+    }
+    let token = match value.split_once(' ') {
+        Some((scheme, token)) if scheme.eq_ignore_ascii_case("Basic") => token.trim_matches(' '),
+        _ => return Err("expected Basic credentials".into()),
+    };
+    let decoded = base64::Engine::decode(&base64::engine::general_purpose::STANDARD, token)
+        .map_err(|e| format!("invalid base64 in credentials: {}", e))?;
+    let decoded =
+        String::from_utf8(decoded).map_err(|_| "credentials aren't valid UTF-8".to_owned())?;
+    let (username, password) = decoded
+        .split_once(':')
+        .ok_or("credentials have no colon separator")?;
+    Ok((username.to_owned(), password.to_owned()))
+}
+
 /// Client for a `Basic` challenge, as in
 /// [RFC 7617](https://datatracker.ietf.org/doc/html/rfc7617).
 ///
@@ -53,6 +170,20 @@ pub struct BasicClient {
 }
 
 impl BasicClient {
+    /// Creates a client from already-unescaped `(key, value)` parameters.
+    ///
+    /// This is an alternative to the `TryFrom<&ChallengeRef<'_>>` impl for
+    /// callers which have parsed the challenge by some other means. Keys are
+    /// compared case-insensitively, as when parsing a challenge.
+    ///
+    /// ```rust
+    /// let client = http_auth::BasicClient::from_params(&[("realm", "foo")]).unwrap();
+    /// assert_eq!(client.realm(), "foo");
+    /// ```
+    pub fn from_params(params: &[(&str, &str)]) -> Result<Self, String> {
+        crate::with_unescaped_params("Basic", params, |c| BasicClient::try_from(c))
+    }
+
     pub fn realm(&self) -> &str {
         &self.realm
     }
@@ -61,10 +192,36 @@ impl BasicClient {
     ///
     /// This is functionally identical to [`encode_credentials`]; no parameters
     /// of the `BasicClient` are needed to produce the credentials.
+    ///
+    /// `BasicClient` doesn't hold the credentials, so it can't cache the
+    /// result. Callers sending many requests with the same credentials can
+    /// call [`encode_credentials`] once and reuse the returned value, avoiding
+    /// the per-request base64 encoding and allocation.
     #[inline]
     pub fn respond(&self, username: &str, password: &str) -> String {
         encode_credentials(username, password)
     }
+
+    /// Responds to the challenge using a non-standard base64 engine.
+    ///
+    /// This is functionally identical to [`encode_credentials_with_engine`].
+    #[inline]
+    pub fn respond_with_engine<E: base64::Engine>(
+        &self,
+        engine: &E,
+        username: &str,
+        password: &str,
+    ) -> String {
+        encode_credentials_with_engine(engine, username, password)
+    }
+
+    /// Responds to the challenge with already-joined `user-pass` credentials.
+    ///
+    /// This is functionally identical to [`encode_userpass`].
+    #[inline]
+    pub fn respond_userpass(&self, user_pass: &str) -> String {
+        encode_userpass(user_pass)
+    }
 }
 
 impl TryFrom<&ChallengeRef<'_>> for BasicClient {
@@ -112,4 +269,60 @@ mod tests {
         };
         assert_eq!(ctx.respond("test", "123\u{A3}"), "Basic dGVzdDoxMjPCow==");
     }
+
+    #[test]
+    fn encode_credential_omits_prefix() {
+        assert_eq!(encode_credential("test", "123\u{A3}"), "dGVzdDoxMjPCow==");
+        assert_eq!(encode_credential("", ""), "Og==");
+        for (u, p) in [("Aladdin", "open sesame"), ("a", "b:c"), ("x", "")] {
+            assert_eq!(
+                format!("Basic {}", encode_credential(u, p)),
+                encode_credentials(u, p)
+            );
+        }
+    }
+
+    #[test]
+    fn empty_realm() {
+        let challenges = crate::parse_challenges(r#"Basic realm="""#).unwrap();
+        let ctx = BasicClient::try_from(&challenges[0]).unwrap();
+        assert_eq!(ctx.realm(), "");
+        assert_eq!(ctx.respond("a", "b"), "Basic YTpi");
+    }
+
+    #[test]
+    fn decode() {
+        assert_eq!(
+            decode_credentials("basic dGVzdDoxMjPCow==").unwrap(),
+            ("test".to_owned(), "123\u{A3}".to_owned())
+        );
+        assert_eq!(
+            decode_credentials(&encode_credentials("a", "b:c")).unwrap(),
+            ("a".to_owned(), "b:c".to_owned())
+        );
+        for bad in [
+            "",
+            "Basic",
+            "Digest QWxhZGRpbjpvcGVuIHNlc2FtZQ==",
+            "Basic !",
+            "Basic YQ==",
+        ] {
+            decode_credentials(bad).unwrap_err();
+        }
+
+        // Rejected on length alone; this isn't valid base64.
+        let long = format!("Basic {}", "!".repeat(DEFAULT_MAX_CREDENTIALS_LEN));
+        let e = decode_credentials(&long).unwrap_err();
+        assert!(e.contains("exceeds limit"), "{}", e);
+        decode_credentials_with_limit("Basic YTpi", 9).unwrap_err();
+        decode_credentials_with_limit("Basic YTpi", 10).unwrap();
+    }
+
+    #[test]
+    fn from_params() {
+        let ctx = BasicClient::from_params(&[("Realm", "a \"quoted\" realm")]).unwrap();
+        assert_eq!(ctx.realm(), "a \"quoted\" realm");
+        BasicClient::from_params(&[]).unwrap_err();
+        BasicClient::from_params(&[("realm", "bad\nrealm")]).unwrap_err();
+    }
 }
@@ -0,0 +1,166 @@
+// Copyright (C) 2026 Scott Lamb <slamb@slamb.org>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! `Bearer` authentication scheme as in
+//! [RFC 6750](https://datatracker.ietf.org/doc/html/rfc6750).
+//!
+//! Obtaining the token, such as via OAuth 2.0, is up to the caller.
+
+use std::convert::TryFrom;
+
+use crate::table::is_token68;
+use crate::ChallengeRef;
+
+/// Encodes the given token.
+///
+/// This can be used to preemptively send `Bearer` authentication, without
+/// sending an unauthenticated request and waiting for a `401 Unauthorized`
+/// response.
+///
+/// The caller should use the returned string as an `Authorization` header
+/// value. Fails if `token` isn't a valid `b64token` as in [RFC 6750 section
+/// 2.1](https://datatracker.ietf.org/doc/html/rfc6750#section-2.1).
+///
+/// ```rust
+/// assert_eq!(
+///     http_auth::bearer::encode("mF_9.B5f-4.1JqM").unwrap(),
+///     "Bearer mF_9.B5f-4.1JqM",
+/// );
+/// http_auth::bearer::encode("two words").unwrap_err();
+/// ```
+pub fn encode(token: &str) -> Result<String, String> {
+    if !is_token68(token) {
+        return Err(format!("invalid bearer token {:?}", token));
+    }
+    Ok(format!("Bearer {}", token))
+}
+
+/// Client for a `Bearer` challenge, as in [RFC 6750 section
+/// 3](https://datatracker.ietf.org/doc/html/rfc6750#section-3).
+///
+/// ```rust
+/// # use std::convert::TryFrom as _;
+/// use http_auth::bearer::BearerClient;
+/// let c = &http_auth::parse_challenges(
+///     r#"Bearer realm="example", error="invalid_token", error_description="expired""#,
+/// )
+/// .unwrap()[0];
+/// let client = BearerClient::try_from(c).unwrap();
+/// assert_eq!(client.realm(), Some("example"));
+/// assert_eq!(client.error(), Some("invalid_token"));
+/// assert_eq!(client.respond("mF_9.B5f-4.1JqM").unwrap(), "Bearer mF_9.B5f-4.1JqM");
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BearerClient {
+    realm: Option<Box<str>>,
+    scope: Option<Box<str>>,
+    error: Option<Box<str>>,
+}
+
+impl BearerClient {
+    /// Creates a client from already-unescaped `(key, value)` parameters.
+    ///
+    /// This is an alternative to the `TryFrom<&ChallengeRef<'_>>` impl for
+    /// callers which have parsed the challenge by some other means. Keys are
+    /// compared case-insensitively, as when parsing a challenge.
+    pub fn from_params(params: &[(&str, &str)]) -> Result<Self, String> {
+        crate::with_unescaped_params("Bearer", params, |c| BearerClient::try_from(c))
+    }
+
+    /// Returns the `realm`, which is optional for this scheme.
+    pub fn realm(&self) -> Option<&str> {
+        self.realm.as_deref()
+    }
+
+    /// Returns the space-delimited `scope` the server requires, if given.
+    pub fn scope(&self) -> Option<&str> {
+        self.scope.as_deref()
+    }
+
+    /// Returns the `error` code, such as `invalid_token`, if given. The
+    /// server omits this when the request had no credentials.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Responds to the challenge with the supplied token.
+    ///
+    /// This is functionally identical to [`encode`]; no parameters of the
+    /// `BearerClient` are needed to produce the credentials.
+    #[inline]
+    pub fn respond(&self, token: &str) -> Result<String, String> {
+        encode(token)
+    }
+}
+
+impl TryFrom<&ChallengeRef<'_>> for BearerClient {
+    type Error = String;
+
+    fn try_from(value: &ChallengeRef<'_>) -> Result<Self, Self::Error> {
+        if !value.scheme.eq_ignore_ascii_case("Bearer") {
+            return Err(format!(
+                "BearerClient doesn't support challenge scheme {:?}",
+                value.scheme
+            ));
+        }
+        let mut client = BearerClient {
+            realm: None,
+            scope: None,
+            error: None,
+        };
+        for (k, v) in &value.params {
+            let field = if k.eq_ignore_ascii_case("realm") {
+                &mut client.realm
+            } else if k.eq_ignore_ascii_case("scope") {
+                &mut client.scope
+            } else if k.eq_ignore_ascii_case("error") {
+                &mut client.error
+            } else {
+                continue;
+            };
+            if field.replace(v.to_unescaped().into()).is_some() {
+                return Err(format!("duplicate parameter {:?}", k));
+            }
+        }
+        Ok(client)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_invalid() {
+        for token in [
+            "",
+            "=",
+            "a b",
+            "a=b",
+            "tok\u{e9}n",
+            "a\r\nSet-Cookie: b",
+            "\"a\"",
+        ] {
+            encode(token).unwrap_err();
+            BearerClient::from_params(&[])
+                .unwrap()
+                .respond(token)
+                .unwrap_err();
+        }
+        assert_eq!(encode("a/b+c==").unwrap(), "Bearer a/b+c==");
+    }
+
+    #[test]
+    fn challenge() {
+        let input = r#"Bearer, Bearer realm="r", scope="openid profile", Basic realm="b""#;
+        let challenges = crate::parse_challenges(input).unwrap();
+        let client = BearerClient::try_from(&challenges[0]).unwrap();
+        assert_eq!(client.realm(), None);
+        assert_eq!(client.error(), None);
+        let client = BearerClient::try_from(&challenges[1]).unwrap();
+        assert_eq!(client.realm(), Some("r"));
+        assert_eq!(client.scope(), Some("openid profile"));
+        BearerClient::try_from(&challenges[2]).unwrap_err();
+        BearerClient::from_params(&[("realm", "a"), ("Realm", "b")]).unwrap_err();
+    }
+}
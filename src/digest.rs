@@ -4,18 +4,49 @@
 //! `Digest` authentication scheme, as in
 //! [RFC 7616](https://datatracker.ietf.org/doc/html/rfc7616).
 
-use std::{convert::TryFrom, fmt::Write as _, io::Write as _};
+use std::{borrow::Cow, convert::TryFrom, fmt::Write as _, io::Write as _};
 
 use digest::Digest;
 
 use crate::{
-    char_classes, ChallengeRef, ParamValue, PasswordParams, C_ATTR, C_ESCAPABLE, C_QDTEXT,
+    append_escaped, char_classes, ChallengeRef, ParamValue, PasswordParams, C_ATTR, C_ESCAPABLE,
+    C_QDTEXT,
 };
 
+/// The minimum client nonce length accepted by [`DigestClient::set_cnonce_len`],
+/// in bytes before hex encoding.
+pub const MIN_CNONCE_LEN: usize = 8;
+
+/// The error returned by [`DigestClient::respond`] and its variants once the
+/// nonce may not be used again, either because the limit set by
+/// [`DigestClient::set_max_reuse`] was reached or because the nonce count
+/// would overflow.
+///
+/// Callers may compare against this to tell that a fresh challenge is needed.
+///
+/// ```rust
+/// use http_auth::{digest::NONCE_COUNT_EXHAUSTED, DigestClient, PasswordParams};
+/// let mut client = DigestClient::from_params(&[("realm", "r"), ("nonce", "n")]).unwrap();
+/// client.set_max_reuse(1);
+/// let p = PasswordParams::new("user", "pass").with_uri("/").with_method("GET");
+/// client.respond(&p).unwrap();
+/// assert_eq!(client.respond(&p).unwrap_err(), NONCE_COUNT_EXHAUSTED);
+/// ```
+pub const NONCE_COUNT_EXHAUSTED: &str = "nonce count exhausted";
+
 /// "Quality of protection" value.
 ///
 /// The values here can be used in a bitmask as in [`DigestClient::qop`].
-#[derive(Copy, Clone, Debug)]
+///
+/// Converts to and from the wire form; parsing is case-insensitive.
+///
+/// ```rust
+/// use http_auth::digest::Qop;
+/// assert_eq!("Auth-Int".parse::<Qop>().unwrap(), Qop::AuthInt);
+/// assert_eq!(Qop::AuthInt.to_string(), "auth-int");
+/// "auth-conf".parse::<Qop>().unwrap_err();
+/// ```
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
 #[repr(u8)]
 #[non_exhaustive]
 pub enum Qop {
@@ -38,6 +69,26 @@ impl Qop {
     }
 }
 
+impl std::fmt::Display for Qop {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for Qop {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("auth") {
+            Ok(Qop::Auth)
+        } else if s.eq_ignore_ascii_case("auth-int") {
+            Ok(Qop::AuthInt)
+        } else {
+            Err(format!("unknown qop {:?}", s))
+        }
+    }
+}
+
 /// A set of zero or more [`Qop`]s.
 #[derive(Copy, Clone, PartialEq, Eq)]
 pub struct QopSet(u8);
@@ -78,22 +129,26 @@ impl std::ops::BitAnd<Qop> for QopSet {
 ///     more CPU-efficient to calculate `H(A1)` only once by supplying the
 ///     username and password at construction time or by caching (username,
 ///     password) -> `H(A1)` mappings internally. `DigestClient` prioritizes
-///     simplicity instead.
-/// *   There's no support yet for parsing the `Authentication-Info` and
+///     simplicity instead. Callers which store `H(A1)` rather than the
+///     password can use [`DigestClient::with_precomputed`].
+/// *   Only partially supports the `Authentication-Info` and
 ///     `Proxy-Authentication-Info` header fields described by [RFC 7616 section
 ///     3.5](https://datatracker.ietf.org/doc/html/rfc7616#section-3.5).
-///     PRs welcome!
-/// *   Always responds using `UTF-8`, and thus doesn't use or keep around the `charset`
-///     parameter. The RFC only allows that parameter to be set to `UTF-8` anyway.
+///     [`DigestClient::process_auth_info`] follows `nextnonce` but doesn't
+///     verify `rspauth`. PRs welcome!
+/// *   Always responds using `UTF-8`. The RFC only allows the `charset`
+///     parameter to be set to `UTF-8`; other values are rejected.
+///     [`DigestClient::charset`] reports whether the server specified it.
 /// *   Supports [RFC 2069](https://datatracker.ietf.org/doc/html/rfc2069) compatibility as in
 ///     [RFC 2617 section 3.2.2.1](https://datatracker.ietf.org/doc/html/rfc2617#section-3.2.2.1),
 ///     even though RFC 7616 drops it. There are still RTSP cameras being sold
-///     in 2021 that use the RFC 2069-style calculations.
+///     in 2021 that use the RFC 2069-style calculations. This mode is used
+///     when `qop` is absent or an empty string.
 /// *   Supports RFC 7616 `userhash`, even though it seems impractical and only
 ///     marginally useful. The server must index the userhash for each supported
 ///     algorithm or calculate it on-the-fly for all users in the database.
-/// *   The `-sess` algorithm variants haven't been tested; there's no example
-///     in the RFCs.
+/// *   The `-sess` algorithm variants haven't been tested against a server;
+///     there's no example in the RFCs.
 ///
 /// ## Security considerations
 ///
@@ -115,7 +170,7 @@ impl std::ops::BitAnd<Qop> for QopSet {
 /// [ONVIF](https://www.onvif.org/profiles/specifications/) mandates the
 /// `Digest` scheme. It doesn't prohibit implementing other schemes, but some
 /// cameras meet the specification's requirement and do no more.
-#[derive(Eq, PartialEq)]
+#[derive(Clone, Eq, PartialEq)]
 pub struct DigestClient {
     /// Holds unescaped versions of all string fields.
     ///
@@ -138,15 +193,56 @@ pub struct DigestClient {
 
     // Non-string fields. See respective methods' doc comments for more information.
     algorithm: Algorithm,
+    algorithm_explicit: bool,
     session: bool,
     stale: bool,
     rfc2069_compat: bool,
     userhash: bool,
+    charset_utf8: bool,
     qop: QopSet,
     nc: u32,
+    max_nc: u32,
+
+    /// The random client nonce used with the current nonce, generated when
+    /// `nc` advances from 0. Only the first `cnonce_len` bytes are sent.
+    cnonce: [u8; 16],
+    cnonce_len: u8,
+
+    /// Credentials supplied via [`DigestClient::set_ha1`] or
+    /// [`DigestClient::with_precomputed`], if any. Boxed to keep the common
+    /// case small.
+    precomputed: Option<Box<Precomputed>>,
+}
+
+/// A validated, lowercase hex `H(A1)` and, if supplied via
+/// [`DigestClient::with_precomputed`], the username it was made for.
+#[derive(Clone, Eq, PartialEq)]
+struct Precomputed {
+    username: Option<Box<str>>,
+    ha1: Box<str>,
 }
 
 impl DigestClient {
+    /// Creates a client from already-unescaped `(key, value)` parameters.
+    ///
+    /// This is an alternative to the `TryFrom<&ChallengeRef<'_>>` impl for
+    /// callers which have parsed the challenge by some other means. Keys are
+    /// compared case-insensitively and values are interpreted exactly as when
+    /// parsing a challenge.
+    ///
+    /// ```rust
+    /// let client = http_auth::DigestClient::from_params(&[
+    ///     ("realm", "http-auth@example.org"),
+    ///     ("qop", "auth, auth-int"),
+    ///     ("algorithm", "SHA-256"),
+    ///     ("nonce", "7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v"),
+    /// ]).unwrap();
+    /// assert_eq!(client.algorithm(), http_auth::digest::Algorithm::Sha256);
+    /// ```
+    pub fn from_params(params: &[(&str, &str)]) -> Result<Self, String> {
+        crate::with_unescaped_params("Digest", params, |c| DigestClient::try_from(c))
+    }
+
     /// Returns a string to be displayed to users so they know which username
     /// and password to use.
     ///
@@ -199,22 +295,77 @@ impl DigestClient {
         self.stale
     }
 
+    /// Returns the `charset` parameter, if the server specified one.
+    ///
+    /// The only value allowed by RFC 7616 is `UTF-8`, which is also what this
+    /// client uses when the parameter is absent.
+    #[inline]
+    pub fn charset(&self) -> Option<&'static str> {
+        if self.charset_utf8 {
+            Some("UTF-8")
+        } else {
+            None
+        }
+    }
+
     /// Returns true if using [RFC 2069](https://datatracker.ietf.org/doc/html/rfc2069)
     /// compatibility mode as in [RFC 2617 section
     /// 3.2.2.1](https://datatracker.ietf.org/doc/html/rfc2617#section-3.2.2.1).
     ///
-    /// If so, `request-digest` is calculated without the nonce count, conce, or qop.
+    /// If so, `request-digest` is calculated without the nonce count, cnonce, or
+    /// qop, and these are omitted from the response. This is the case when the
+    /// challenge has no `qop` parameter.
+    ///
+    /// ```rust
+    /// # use http_auth::digest::DigestClient;
+    /// let client = DigestClient::from_params(&[("realm", "r"), ("nonce", "n")]).unwrap();
+    /// assert!(client.rfc2069_compat());
+    /// let client =
+    ///     DigestClient::from_params(&[("realm", "r"), ("nonce", "n"), ("qop", "auth")]).unwrap();
+    /// assert!(!client.rfc2069_compat());
+    /// ```
     #[inline]
     pub fn rfc2069_compat(&self) -> bool {
         self.rfc2069_compat
     }
 
     /// Returns the algorithm used to produce the digest and an unkeyed digest.
+    ///
+    /// If the challenge has no `algorithm` parameter, this is
+    /// [`Algorithm::Md5`], as specified in [RFC 7616 section
+    /// 3.3](https://datatracker.ietf.org/doc/html/rfc7616#section-3.3). See
+    /// [`DigestClient::algorithm_was_explicit`] to distinguish this case.
     #[inline]
     pub fn algorithm(&self) -> Algorithm {
         self.algorithm
     }
 
+    /// Returns the algorithm as written in the response's `algorithm`
+    /// parameter, including the `-sess` suffix if applicable.
+    ///
+    /// ```rust
+    /// let client = http_auth::DigestClient::from_params(&[
+    ///     ("realm", "r"),
+    ///     ("nonce", "n"),
+    ///     ("algorithm", "SHA-256-sess"),
+    /// ]).unwrap();
+    /// assert_eq!(client.algorithm_str(), "SHA-256-sess");
+    /// ```
+    #[inline]
+    pub fn algorithm_str(&self) -> &'static str {
+        self.algorithm.as_str(self.session)
+    }
+
+    /// Returns true if the challenge specified the `algorithm` parameter,
+    /// false if [`DigestClient::algorithm`] is the `MD5` default.
+    ///
+    /// This allows a local policy to accept an explicit `MD5` while refusing
+    /// to fall back to it implicitly.
+    #[inline]
+    pub fn algorithm_was_explicit(&self) -> bool {
+        self.algorithm_explicit
+    }
+
     /// Returns if the session style `A1` will be used.
     #[inline]
     pub fn session(&self) -> bool {
@@ -227,6 +378,25 @@ impl DigestClient {
         self.qop
     }
 
+    /// Returns true if the challenge offers the given `qop`.
+    ///
+    /// This allows deciding whether to buffer the request body for
+    /// [`Qop::AuthInt`] before calling [`DigestClient::respond`]. As with
+    /// [`DigestClient::qop`], a challenge without `qop` is treated as offering
+    /// [`Qop::Auth`].
+    ///
+    /// ```rust
+    /// use http_auth::digest::{DigestClient, Qop};
+    /// let client =
+    ///     DigestClient::from_params(&[("realm", "r"), ("nonce", "n"), ("qop", "auth-int")]).unwrap();
+    /// assert!(client.supports_qop(Qop::AuthInt));
+    /// assert!(!client.supports_qop(Qop::Auth));
+    /// ```
+    #[inline]
+    pub fn supports_qop(&self, qop: Qop) -> bool {
+        self.qop & qop
+    }
+
     /// Returns the number of times the server-supplied nonce has been used by
     /// [`DigestClient::respond`].
     #[inline]
@@ -234,13 +404,293 @@ impl DigestClient {
         self.nc
     }
 
+    /// Resets the nonce count, so the next [`DigestClient::respond`] call sends
+    /// `nc=00000001` with a fresh client nonce.
+    ///
+    /// This is appropriate when the server will treat the next request as the
+    /// first use of the nonce, such as after reconnecting to a server which
+    /// tracks nonce counts per connection. Otherwise, a server which checks
+    /// for replays will reject the reused nonce counts.
+    #[inline]
+    pub fn reset_nonce_count(&mut self) {
+        self.nc = 0;
+    }
+
+    /// Replaces this client's challenge with a new one, such as from a later
+    /// `401 Unauthorized` response, returning true if the nonce is unchanged.
+    ///
+    /// Fails, leaving this client unchanged, if the new challenge has a
+    /// different realm. That indicates a different protection space, which
+    /// may need different credentials; build a new client instead. A
+    /// precomputed `H(A1)` from [`DigestClient::set_ha1`] or
+    /// [`DigestClient::with_precomputed`] is kept, so this likewise fails if
+    /// the new challenge's algorithm would need a different `H(A1)`.
+    ///
+    /// When the server re-sends the same nonce, restarting the
+    /// nonce count would reuse `nc` values the server has already seen, so
+    /// this keeps the nonce count and client nonce. Otherwise, it resets them
+    /// as with a freshly constructed client. In either case, the limit set by
+    /// [`DigestClient::set_max_reuse`] is kept.
+    ///
+    /// By contrast, building a new client (as via
+    /// [`crate::PasswordClientBuilder`]) always starts at `nc=00000001`.
+    ///
+    /// ```rust
+    /// # use std::convert::TryFrom as _;
+    /// use http_auth::{parse_challenges, DigestClient};
+    /// let header = r#"Digest realm="r", nonce="n", qop="auth""#;
+    /// let c = &parse_challenges(header).unwrap()[0];
+    /// let mut client = DigestClient::try_from(c).unwrap();
+    /// client.set_nonce_count(3).unwrap();
+    /// assert!(client.update_from_challenge(c).unwrap());
+    /// assert_eq!(client.nonce_count(), 3);
+    /// ```
+    pub fn update_from_challenge(&mut self, challenge: &ChallengeRef<'_>) -> Result<bool, String> {
+        let mut new = DigestClient::try_from(challenge)?;
+        if new.realm() != self.realm() {
+            return Err(format!(
+                "realm changed from {:?} to {:?}",
+                self.realm(),
+                new.realm()
+            ));
+        }
+        if self.precomputed.is_some() && new.algorithm != self.algorithm {
+            return Err(format!(
+                "algorithm changed from {} to {}, so the precomputed H(A1) doesn't apply",
+                self.algorithm.as_str(false),
+                new.algorithm.as_str(false)
+            ));
+        }
+        let same_nonce = new.nonce() == self.nonce();
+        if same_nonce {
+            new.nc = self.nc;
+            new.cnonce = self.cnonce;
+        }
+        new.max_nc = self.max_nc;
+        new.cnonce_len = self.cnonce_len;
+        new.precomputed = self.precomputed.take();
+        *self = new;
+        Ok(same_nonce)
+    }
+
+    /// Restores a nonce count, such as one persisted with a cached session via
+    /// [`DigestClient::nonce_count`].
+    ///
+    /// Fails if `nc` is lower than the current nonce count; reusing a nonce
+    /// count would allow the server to mistake the request for a replay. The
+    /// next [`DigestClient::respond`] call sends `nc + 1`. It uses the same
+    /// client nonce as earlier responses to this nonce, if any, so that the
+    /// `-sess` algorithm variants keep the same session `H(A1)`; otherwise it
+    /// uses a fresh one.
+    pub fn set_nonce_count(&mut self, nc: u32) -> Result<(), String> {
+        if nc < self.nc {
+            return Err(format!(
+                "nonce count {} is lower than current nonce count {}",
+                nc, self.nc
+            ));
+        }
+        if self.nc == 0 && nc > 0 {
+            self.cnonce = rand::random();
+        }
+        self.nc = nc;
+        Ok(())
+    }
+
+    /// Returns the client nonce used by [`DigestClient::respond`] for the
+    /// current nonce, or `None` if it hasn't been used yet.
+    ///
+    /// For the `-sess` algorithm variants, `A1` depends on this value. To
+    /// persist a session, save the challenge, this value, and
+    /// [`DigestClient::nonce_count`]; to restore it, rebuild the client from
+    /// the challenge and call [`DigestClient::resume_session`].
+    pub fn client_nonce(&self) -> Option<String> {
+        if self.nc == 0 {
+            return None;
+        }
+        Some(self.cnonce_hex())
+    }
+
+    /// Returns the hex-encoded client nonce as sent.
+    fn cnonce_hex(&self) -> String {
+        hex::encode(&self.cnonce[..usize::from(self.cnonce_len)])
+    }
+
+    /// Returns the client nonce for the next response, generating a fresh one
+    /// if this is the first use of the current nonce.
+    fn next_cnonce(&mut self) -> String {
+        if self.nc == 0 {
+            self.cnonce = rand::random();
+        }
+        self.cnonce_hex()
+    }
+
+    /// Returns the number of random bytes in the client nonce, as set by
+    /// [`DigestClient::set_cnonce_len`].
+    #[inline]
+    pub fn cnonce_len(&self) -> usize {
+        usize::from(self.cnonce_len)
+    }
+
+    /// Sets the number of random bytes in the client nonce, which is sent
+    /// hex-encoded, so its length is twice this.
+    ///
+    /// The default is 16 bytes (128 bits), which is also the maximum. Shorter
+    /// client nonces are only for servers which reject the default, and
+    /// [`MIN_CNONCE_LEN`] bytes is the minimum; the client nonce protects
+    /// against chosen-plaintext attacks by the server, which requires that it
+    /// be unpredictable.
+    ///
+    /// Fails if `len` is out of range or if the current nonce has already been
+    /// used, as the client nonce must not change within a session.
+    ///
+    /// ```rust
+    /// # use http_auth::{DigestClient, PasswordParams};
+    /// let mut client =
+    ///     DigestClient::from_params(&[("realm", "r"), ("nonce", "n"), ("qop", "auth")]).unwrap();
+    /// client.set_cnonce_len(4).unwrap_err();
+    /// client.set_cnonce_len(8).unwrap();
+    /// client.respond(&PasswordParams::new("u", "p").with_uri("/").with_method("GET")).unwrap();
+    /// assert_eq!(client.client_nonce().unwrap().len(), 16);
+    /// ```
+    pub fn set_cnonce_len(&mut self, len: usize) -> Result<(), String> {
+        if !(MIN_CNONCE_LEN..=16).contains(&len) {
+            return Err(format!(
+                "client nonce length {} not in [{}, 16]",
+                len, MIN_CNONCE_LEN
+            ));
+        }
+        if self.nc != 0 && usize::from(self.cnonce_len) != len {
+            return Err("can't change client nonce length after the nonce is used".into());
+        }
+        self.cnonce_len = len as u8;
+        Ok(())
+    }
+
+    /// Restores the nonce count and client nonce of a persisted session, so
+    /// subsequent responses are identical to those of the original client.
+    ///
+    /// `cnonce` must be a value returned by [`DigestClient::client_nonce`].
+    /// As with [`DigestClient::set_nonce_count`], fails if `nc` is lower than
+    /// the current nonce count.
+    pub fn resume_session(&mut self, nc: u32, cnonce: &str) -> Result<(), String> {
+        let mut decoded = [0u8; 16];
+        hex::decode_to_slice(cnonce, &mut decoded[..usize::from(self.cnonce_len)])
+            .map_err(|e| format!("bad client nonce {:?}: {}", cnonce, e))?;
+        if nc == 0 {
+            return Err("can't resume session with nonce count 0".into());
+        }
+        self.set_nonce_count(nc)?;
+        self.cnonce = decoded;
+        Ok(())
+    }
+
+    /// Processes an `Authentication-Info` or `Proxy-Authentication-Info` header
+    /// value from the server's response.
+    ///
+    /// If the server supplied a `nextnonce`, switches to it and resets the
+    /// nonce count, so subsequent [`DigestClient::respond`] calls use the new
+    /// nonce. Other parameters are currently ignored; in particular, `rspauth`
+    /// isn't verified.
+    ///
+    /// ```rust
+    /// let mut client = http_auth::DigestClient::from_params(&[
+    ///     ("realm", "r"),
+    ///     ("nonce", "a"),
+    ///     ("qop", "auth"),
+    /// ]).unwrap();
+    /// client.process_auth_info(r#"nextnonce="b", qop=auth"#).unwrap();
+    /// assert_eq!(client.nonce(), "b");
+    /// ```
+    pub fn process_auth_info(&mut self, value: &str) -> Result<(), String> {
+        let info: AuthenticationInfo = value.parse()?;
+        if let Some(nextnonce) = info.nextnonce {
+            let nonce_start = usize::from(self.nonce_start);
+            let mut buf = String::with_capacity(nonce_start + nextnonce.len());
+            buf.push_str(&self.buf[..nonce_start]);
+            buf.push_str(&nextnonce);
+            self.buf = buf.into_boxed_str();
+            self.nc = 0;
+        }
+        Ok(())
+    }
+
+    /// Returns the maximum number of times the nonce may be used, as set by
+    /// [`DigestClient::set_max_reuse`].
+    #[inline]
+    pub fn max_reuse(&self) -> u32 {
+        self.max_nc
+    }
+
+    /// Limits the number of times [`DigestClient::respond`] will use the nonce.
+    ///
+    /// This is useful when the server's nonce lifetime policy is known out of
+    /// band: once the limit is reached, `respond` fails, and the caller should
+    /// obtain a fresh challenge rather than sending a request the server will
+    /// reject. The error is then [`NONCE_COUNT_EXHAUSTED`], distinguishing
+    /// this case from others; [`DigestClient::reuse_exhausted`] checks for it
+    /// in advance.
+    ///
+    /// The default is `u32::MAX`, meaning unlimited until the nonce count
+    /// overflows.
+    #[inline]
+    pub fn set_max_reuse(&mut self, n: u32) {
+        self.max_nc = n;
+    }
+
+    /// Returns true if the nonce has been used the maximum number of times, so
+    /// [`DigestClient::respond`] will fail until a fresh challenge is supplied.
+    #[inline]
+    pub fn reuse_exhausted(&self) -> bool {
+        self.nc >= self.max_nc
+    }
+
     /// Responds to the challenge with the supplied parameters.
     ///
     /// The caller should use the returned string as an `Authorization` or
     /// `Proxy-Authorization` header value.
+    ///
+    /// The client nonce (`cnonce`) is generated randomly on the first call and
+    /// reused for subsequent calls with the same nonce, so that successive
+    /// responses differ only in nonce count (`nc`) and `response`. This is
+    /// necessary for the `-sess` algorithm variants, which as described in
+    /// [RFC 7616 section
+    /// 3.4.2](https://datatracker.ietf.org/doc/html/rfc7616#section-3.4.2)
+    /// calculate `A1` from the first client nonce.
+    ///
+    /// `H(A1)` is recomputed on each call rather than cached. Unless supplied
+    /// via [`DigestClient::set_ha1`] or [`DigestClient::with_precomputed`],
+    /// the client doesn't retain credentials, and noticing changed credentials would
+    /// mean keeping either the password or the password-equivalent `H(A1)`
+    /// for longer than necessary. The savings would be small: one of the three
+    /// or four hash operations per response, each over a short input.
     #[inline]
     pub fn respond(&mut self, p: &PasswordParams) -> Result<String, String> {
-        self.respond_inner(p, &new_random_cnonce())
+        let mut out = String::with_capacity(128);
+        self.respond_into(p, &mut out)?;
+        Ok(out)
+    }
+
+    /// Responds as in [`DigestClient::respond`], appending to `out` rather
+    /// than allocating a new `String`.
+    ///
+    /// This allows reusing one buffer across requests. On error, `out` is
+    /// left as it was.
+    ///
+    /// ```rust
+    /// # use http_auth::{DigestClient, PasswordParams};
+    /// let mut client =
+    ///     DigestClient::from_params(&[("realm", "r"), ("nonce", "n"), ("qop", "auth")]).unwrap();
+    /// let mut buf = String::new();
+    /// for uri in ["/a", "/b"] {
+    ///     buf.clear();
+    ///     let p = PasswordParams::new("user", "pass").with_uri(uri).with_method("GET");
+    ///     client.respond_into(&p, &mut buf).unwrap();
+    ///     assert!(buf.starts_with("Digest "));
+    /// }
+    /// ```
+    pub fn respond_into(&mut self, p: &PasswordParams, out: &mut String) -> Result<(), String> {
+        let cnonce = self.next_cnonce();
+        self.respond_inner(p, &cnonce, out)
     }
 
     /// Responds using a fixed cnonce **for testing only**.
@@ -253,7 +703,9 @@ impl DigestClient {
         p: &PasswordParams,
         cnonce: &str,
     ) -> Result<String, String> {
-        self.respond_inner(p, cnonce)
+        let mut out = String::with_capacity(128);
+        self.respond_inner(p, cnonce, &mut out)?;
+        Ok(out)
     }
 
     /// Helper for respond methods.
@@ -261,15 +713,146 @@ impl DigestClient {
     /// We don't simply implement this as `respond_with_testing_cnonce` and have
     /// `respond` delegate to that method because it'd be confusing/alarming if
     /// that method name ever shows up in production stack traces.
-    fn respond_inner(&mut self, p: &PasswordParams, cnonce: &str) -> Result<String, String> {
-        let realm = self.realm();
-        let mut h_a1 = self.algorithm.h(&[
-            p.username.as_bytes(),
-            b":",
-            realm.as_bytes(),
-            b":",
-            p.password.as_bytes(),
-        ]);
+    fn respond_inner(
+        &mut self,
+        p: &PasswordParams,
+        cnonce: &str,
+        out: &mut String,
+    ) -> Result<(), String> {
+        let start = out.len();
+        let (p, qop, nc, response) = self.next_response(p, cnonce)?;
+        match self.append_response(&p, cnonce, qop, nc, &response, out) {
+            Ok(()) => {
+                self.nc = nc;
+                Ok(())
+            }
+            Err(e) => {
+                out.truncate(start);
+                Err(e)
+            }
+        }
+    }
+
+    /// Computes the response for the next request as in
+    /// [`DigestClient::compute_response`], after substituting credentials as
+    /// in [`DigestClient::credentials`].
+    ///
+    /// Returns the effective parameters along with the computed values.
+    fn next_response<'a>(
+        &'a self,
+        p: &PasswordParams<'a>,
+        cnonce: &str,
+    ) -> Result<(PasswordParams<'a>, Qop, u32, String), String> {
+        let (p, ha1) = self.credentials(p);
+        let (qop, nc, response) = self.compute_response(&p, cnonce, ha1)?;
+        Ok((p, qop, nc, response))
+    }
+
+    /// Substitutes the stored credentials from [`DigestClient::set_ha1`] or
+    /// [`DigestClient::with_precomputed`], if any.
+    fn credentials<'a>(&'a self, p: &PasswordParams<'a>) -> (PasswordParams<'a>, Option<&'a str>) {
+        match &self.precomputed {
+            Some(c) => (
+                PasswordParams {
+                    username: c.username.as_deref().unwrap_or(p.username),
+                    password: "",
+                    ..*p
+                },
+                Some(&c.ha1),
+            ),
+            None => (*p, None),
+        }
+    }
+
+    /// Appends the response to `out`, possibly leaving partial output on error.
+    fn append_response(
+        &self,
+        p: &PasswordParams,
+        cnonce: &str,
+        qop: Qop,
+        nc: u32,
+        response: &str,
+        out: &mut String,
+    ) -> Result<(), String> {
+        let mut hex_nc = [0u8; 8];
+        let _ = write!(&mut hex_nc[..], "{:08x}", nc);
+        let str_hex_nc = match std::str::from_utf8(&hex_nc[..]) {
+            Ok(h) => h,
+            Err(_) => unreachable!(),
+        };
+
+        out.push_str("Digest ");
+        if self.userhash {
+            let hashed = self
+                .algorithm
+                .h(&[p.username.as_bytes(), b":", self.realm().as_bytes()]);
+            append_quoted_key_value(out, "username", &hashed)?;
+            append_unquoted_key_value(out, "userhash", "true");
+        } else if is_valid_quoted_value(p.username) {
+            append_quoted_key_value(out, "username", p.username)?;
+        } else {
+            append_extended_key_value(out, "username", p.username);
+        }
+        append_quoted_key_value(out, "realm", self.realm())?;
+        append_quoted_key_value(out, "uri", p.uri)?;
+        append_quoted_key_value(out, "nonce", self.nonce())?;
+        if !self.rfc2069_compat {
+            append_unquoted_key_value(out, "algorithm", self.algorithm.as_str(self.session));
+            append_unquoted_key_value(out, "nc", str_hex_nc);
+            append_quoted_key_value(out, "cnonce", cnonce)?;
+            append_unquoted_key_value(out, "qop", qop.as_str());
+        }
+        append_quoted_key_value(out, "response", response)?;
+        if let Some(o) = self.opaque() {
+            append_quoted_key_value(out, "opaque", o)?;
+        }
+        out.truncate(out.len() - 2); // remove final ", "
+        Ok(())
+    }
+
+    /// Returns the `response` parameter value for the request described by
+    /// `p`, without the rest of the `Authorization` header value.
+    ///
+    /// This is for callers which assemble the header value themselves or
+    /// check the digest against a reference. Like [`DigestClient::respond`],
+    /// it uses the nonce once; afterward, [`DigestClient::nonce_count`] and
+    /// [`DigestClient::client_nonce`] return the `nc` and `cnonce` values the
+    /// digest was computed with.
+    pub fn response_digest(&mut self, p: &PasswordParams) -> Result<String, String> {
+        let cnonce = self.next_cnonce();
+        let (_, _, nc, response) = self.next_response(p, &cnonce)?;
+        self.nc = nc;
+        Ok(response)
+    }
+
+    /// Validates a hex-encoded `H(A1)` for this client's algorithm, returning
+    /// it in lowercase.
+    fn check_ha1(&self, ha1: &str) -> Result<String, String> {
+        let len = self.algorithm.h_empty().len();
+        if ha1.len() != len || !ha1.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(format!(
+                "H(A1) for {} should be {} hex digits",
+                self.algorithm.as_str(false),
+                len
+            ));
+        }
+        Ok(ha1.to_ascii_lowercase())
+    }
+
+    /// Computes the qop, nonce count, and `response` value for the next
+    /// request, without updating the nonce count.
+    ///
+    /// Uses the precomputed `ha1` if supplied, rather than `p.password`.
+    fn compute_response(
+        &self,
+        p: &PasswordParams,
+        cnonce: &str,
+        ha1: Option<&str>,
+    ) -> Result<(Qop, u32, String), String> {
+        let mut h_a1 = match ha1 {
+            Some(h) => self.check_ha1(h)?,
+            None => compute_ha1(p.username, self.realm(), p.password, self.algorithm),
+        };
         if self.session {
             h_a1 = self.algorithm.h(&[
                 h_a1.as_bytes(),
@@ -284,9 +867,17 @@ impl DigestClient {
         // [https://datatracker.ietf.org/doc/html/rfc7616#section-3.4.3].
         let (h_a2, qop);
         if let (Some(body), true) = (p.body, self.qop & Qop::AuthInt) {
-            h_a2 = self
-                .algorithm
-                .h(&[p.method.as_bytes(), b":", p.uri.as_bytes(), b":", body]);
+            let h_body = match body {
+                [] => Cow::Borrowed(self.algorithm.h_empty()),
+                _ => Cow::Owned(self.algorithm.h(&[body])),
+            };
+            h_a2 = self.algorithm.h(&[
+                p.method.as_bytes(),
+                b":",
+                p.uri.as_bytes(),
+                b":",
+                h_body.as_bytes(),
+            ]);
             qop = Qop::AuthInt;
         } else if self.qop & Qop::Auth {
             h_a2 = self
@@ -297,13 +888,12 @@ impl DigestClient {
             return Err("no supported/available qop".into());
         }
 
-        let nc = self.nc.checked_add(1).ok_or("nonce count exhausted")?;
+        if self.reuse_exhausted() {
+            return Err(NONCE_COUNT_EXHAUSTED.into());
+        }
+        let nc = self.nc + 1;
         let mut hex_nc = [0u8; 8];
         let _ = write!(&mut hex_nc[..], "{:08x}", nc);
-        let str_hex_nc = match std::str::from_utf8(&hex_nc[..]) {
-            Ok(h) => h,
-            Err(_) => unreachable!(),
-        };
 
         // https://datatracker.ietf.org/doc/html/rfc2617#section-3.2.2.1
         let response = if self.rfc2069_compat {
@@ -329,36 +919,7 @@ impl DigestClient {
                 h_a2.as_bytes(),
             ])
         };
-
-        let mut out = String::with_capacity(128);
-        out.push_str("Digest ");
-        if self.userhash {
-            let hashed = self
-                .algorithm
-                .h(&[p.username.as_bytes(), b":", realm.as_bytes()]);
-            append_quoted_key_value(&mut out, "username", &hashed)?;
-            append_unquoted_key_value(&mut out, "userhash", "true");
-        } else if is_valid_quoted_value(p.username) {
-            append_quoted_key_value(&mut out, "username", p.username)?;
-        } else {
-            append_extended_key_value(&mut out, "username", p.username);
-        }
-        append_quoted_key_value(&mut out, "realm", self.realm())?;
-        append_quoted_key_value(&mut out, "uri", p.uri)?;
-        append_quoted_key_value(&mut out, "nonce", self.nonce())?;
-        if !self.rfc2069_compat {
-            append_unquoted_key_value(&mut out, "algorithm", self.algorithm.as_str(self.session));
-            append_unquoted_key_value(&mut out, "nc", str_hex_nc);
-            append_quoted_key_value(&mut out, "cnonce", cnonce)?;
-            append_unquoted_key_value(&mut out, "qop", qop.as_str());
-        }
-        append_quoted_key_value(&mut out, "response", &response)?;
-        if let Some(o) = self.opaque() {
-            append_quoted_key_value(&mut out, "opaque", o)?;
-        }
-        out.truncate(out.len() - 2); // remove final ", "
-        self.nc = nc;
-        Ok(out)
+        Ok((qop, nc, response))
     }
 }
 
@@ -366,6 +927,105 @@ impl TryFrom<&ChallengeRef<'_>> for DigestClient {
     type Error = String;
 
     fn try_from(value: &ChallengeRef<'_>) -> Result<Self, Self::Error> {
+        DigestClient::from_challenge(value, false)
+    }
+}
+
+impl DigestClient {
+    /// Creates a client as with the `TryFrom<&ChallengeRef<'_>>` impl, but
+    /// fails if the challenge has any parameter not defined in [RFC 7616
+    /// section 3.3](https://datatracker.ietf.org/doc/html/rfc7616#section-3.3).
+    ///
+    /// By default, unknown parameters are ignored for interoperability. This
+    /// stricter mode is for deployments which would rather reject anything
+    /// unexpected. See also
+    /// [`crate::PasswordClientBuilder::strict_digest_params`].
+    ///
+    /// ```rust
+    /// # use std::convert::TryFrom as _;
+    /// use http_auth::{parse_challenges, DigestClient};
+    /// let c = &parse_challenges(r#"Digest realm="r", nonce="n", foo=bar"#).unwrap()[0];
+    /// DigestClient::try_from(c).unwrap();
+    /// DigestClient::try_from_strict(c).unwrap_err();
+    /// ```
+    pub fn try_from_strict(challenge: &ChallengeRef<'_>) -> Result<Self, String> {
+        DigestClient::from_challenge(challenge, true)
+    }
+
+    /// Creates a client which responds with a stored username and `H(A1)`
+    /// rather than a password.
+    ///
+    /// `ha1` is the hex-encoded `H(username ":" realm ":" password)` for the
+    /// challenge's realm and algorithm, such as an MD5 hash from an Apache
+    /// `.htdigest` file. [`DigestClient::respond`] and its variants then ignore
+    /// [`PasswordParams::username`] and [`PasswordParams::password`].
+    ///
+    /// For the `-sess` algorithm variants, `ha1` is the hash before the
+    /// session step: the client derives the session `H(A1)` from it, the
+    /// nonce, and the client nonce on each response.
+    ///
+    /// Unlike other clients, this one retains the password-equivalent `ha1`
+    /// for its lifetime; its `Debug` output shows only the username. Fails if
+    /// `ha1` isn't the right length for the challenge's algorithm.
+    ///
+    /// ```rust
+    /// use http_auth::{parse_challenges, DigestClient, PasswordParams};
+    /// let c = &parse_challenges(r#"Digest realm="r", nonce="n""#).unwrap()[0];
+    /// let ha1 = "c54f00aa3213149a57ce6ce3f0365957"; // MD5("Mufasa:r:Circle of Life")
+    /// let mut client = DigestClient::with_precomputed(c, "Mufasa", ha1).unwrap();
+    /// let p = PasswordParams::new("", "").with_uri("/").with_method("GET");
+    /// assert_eq!(
+    ///     client.respond(&p).unwrap(),
+    ///     r#"Digest username="Mufasa", realm="r", uri="/", nonce="n", response="35a26c340ec2db75273c40da54213df2""#
+    /// );
+    /// ```
+    pub fn with_precomputed(
+        challenge: &ChallengeRef<'_>,
+        username: &str,
+        ha1: &str,
+    ) -> Result<Self, String> {
+        let mut client = DigestClient::try_from(challenge)?;
+        client.set_ha1(ha1)?;
+        if let Some(c) = client.precomputed.as_mut() {
+            c.username = Some(username.into());
+        }
+        Ok(client)
+    }
+
+    /// Stores a precomputed `H(A1)` to use rather than the password.
+    ///
+    /// `ha1` is the hex-encoded `H(username ":" realm ":" password)` for this
+    /// challenge's realm and algorithm, such as an MD5 hash from an Apache
+    /// `.htdigest` file. This allows a credential store to keep only that
+    /// value, never the password. [`DigestClient::respond`] and its variants
+    /// then ignore [`PasswordParams::password`] but still use
+    /// [`PasswordParams::username`], unless the client was created via
+    /// [`DigestClient::with_precomputed`]. For the `-sess` algorithm
+    /// variants, `ha1` is the hash before the session step, which is still
+    /// applied.
+    ///
+    /// The client retains `ha1` for its lifetime. Fails, leaving the client
+    /// unchanged, if `ha1` isn't the right length for the algorithm.
+    ///
+    /// ```rust
+    /// # use http_auth::{DigestClient, PasswordParams};
+    /// let params = [("realm", "r"), ("nonce", "n")];
+    /// let p = PasswordParams::new("Mufasa", "Circle of Life").with_uri("/").with_method("GET");
+    /// let mut a = DigestClient::from_params(&params).unwrap();
+    /// let mut b = a.clone();
+    /// let from_password = a.respond(&p).unwrap();
+    /// b.set_ha1("c54f00aa3213149a57ce6ce3f0365957").unwrap(); // MD5("Mufasa:r:Circle of Life")
+    /// let p = PasswordParams::new("Mufasa", "").with_uri("/").with_method("GET");
+    /// assert_eq!(b.respond(&p).unwrap(), from_password);
+    /// ```
+    pub fn set_ha1(&mut self, ha1: &str) -> Result<(), String> {
+        let ha1 = self.check_ha1(ha1)?.into();
+        let username = self.precomputed.take().and_then(|c| c.username);
+        self.precomputed = Some(Box::new(Precomputed { username, ha1 }));
+        Ok(())
+    }
+
+    pub(crate) fn from_challenge(value: &ChallengeRef<'_>, strict: bool) -> Result<Self, String> {
         if !value.scheme.eq_ignore_ascii_case("Digest") {
             return Err(format!(
                 "DigestClientContext doesn't support challenge scheme {:?}",
@@ -382,26 +1042,30 @@ impl TryFrom<&ChallengeRef<'_>> for DigestClient {
         let mut algorithm_and_session = None;
         let mut qop_str = None;
         let mut userhash_str = None;
+        let mut charset_str = None;
 
         // Parse response header field parameters as in
         // [https://datatracker.ietf.org/doc/html/rfc7616#section-3.3].
         for (k, v) in &value.params {
-            // Note that "stale" and "algorithm" can be directly compared
-            // without unescaping because RFC 7616 section 3.3 says "For
-            // historical reasons, a sender MUST NOT generate the quoted string
-            // syntax values for the following parameters: stale and algorithm."
+            // RFC 7616 section 3.3 says "For historical reasons, a sender
+            // MUST NOT generate the quoted string syntax values for the
+            // following parameters: stale and algorithm." Some nonconforming
+            // servers do anyway, so accept it, unescaping if necessary.
             if store_param(k, v, "realm", &mut realm, &mut buf_len)?
                 || store_param(k, v, "domain", &mut domain, &mut buf_len)?
                 || store_param(k, v, "nonce", &mut nonce, &mut buf_len)?
                 || store_param(k, v, "opaque", &mut opaque, &mut buf_len)?
                 || store_param(k, v, "qop", &mut qop_str, &mut unused_len)?
                 || store_param(k, v, "userhash", &mut userhash_str, &mut unused_len)?
+                || store_param(k, v, "charset", &mut charset_str, &mut unused_len)?
             {
                 // Do nothing here.
             } else if k.eq_ignore_ascii_case("stale") {
-                stale = v.escaped.eq_ignore_ascii_case("true");
+                stale = v.unescaped_cow().eq_ignore_ascii_case("true");
             } else if k.eq_ignore_ascii_case("algorithm") {
-                algorithm_and_session = Some(Algorithm::parse(v.escaped)?);
+                algorithm_and_session = Some(Algorithm::parse(&v.unescaped_cow())?);
+            } else if strict {
+                return Err(format!("unexpected parameter {:?}", k));
             }
         }
         let realm = realm.ok_or("missing required parameter realm")?;
@@ -414,31 +1078,35 @@ impl TryFrom<&ChallengeRef<'_>> for DigestClient {
             ));
         }
 
+        let algorithm_explicit = algorithm_and_session.is_some();
         let algorithm_and_session = algorithm_and_session.unwrap_or((Algorithm::Md5, false));
 
         let mut buf = String::with_capacity(buf_len);
         let mut qop = QopSet(0);
-        let rfc2069_compat = if let Some(qop_str) = qop_str {
-            let qop_str = qop_str.unescaped_with_scratch(&mut buf);
-            for v in qop_str.split(',') {
-                let v = v.trim();
-                if v.eq_ignore_ascii_case("auth") {
-                    qop.0 |= Qop::Auth as u8;
-                } else if v.eq_ignore_ascii_case("auth-int") {
-                    qop.0 |= Qop::AuthInt as u8;
+        let rfc2069_compat = match qop_str.map(|q| q.unescaped_with_scratch(&mut buf)) {
+            // Some servers send a malformed `qop=""`; treat it as absent.
+            Some(qop_str) if !qop_str.trim().is_empty() => {
+                for v in qop_str.split(',') {
+                    let v = v.trim();
+                    if v.eq_ignore_ascii_case("auth") {
+                        qop.0 |= Qop::Auth as u8;
+                    } else if v.eq_ignore_ascii_case("auth-int") {
+                        qop.0 |= Qop::AuthInt as u8;
+                    }
+                }
+                if qop.0 == 0 {
+                    return Err(format!("no supported qop in {:?}", qop_str));
                 }
+                false
             }
-            if qop.0 == 0 {
-                return Err(format!("no supported qop in {:?}", qop_str));
+            _ => {
+                // An absent qop is treated as "auth", according to
+                // https://datatracker.ietf.org/doc/html/rfc7616#section-3.4.3
+                qop.0 |= Qop::Auth as u8;
+                true
             }
-            buf.clear();
-            false
-        } else {
-            // An absent qop is treated as "auth", according to
-            // https://datatracker.ietf.org/doc/html/rfc7616#section-3.4.3
-            qop.0 |= Qop::Auth as u8;
-            true
         };
+        buf.clear();
         let userhash;
         if let Some(userhash_str) = userhash_str {
             let userhash_str = userhash_str.unescaped_with_scratch(&mut buf);
@@ -447,6 +1115,16 @@ impl TryFrom<&ChallengeRef<'_>> for DigestClient {
         } else {
             userhash = false;
         };
+        let charset_utf8 = if let Some(charset_str) = charset_str {
+            let charset_str = charset_str.unescaped_with_scratch(&mut buf);
+            if !charset_str.eq_ignore_ascii_case("UTF-8") {
+                return Err(format!("unsupported charset {:?}", charset_str));
+            }
+            buf.clear();
+            true
+        } else {
+            false
+        };
         realm.append_unescaped(&mut buf);
         let domain_start = buf.len();
         if let Some(d) = domain {
@@ -464,12 +1142,18 @@ impl TryFrom<&ChallengeRef<'_>> for DigestClient {
             opaque_start: opaque_start as u16,
             nonce_start: nonce_start as u16,
             algorithm: algorithm_and_session.0,
+            algorithm_explicit,
             session: algorithm_and_session.1,
             stale,
             rfc2069_compat,
             userhash,
+            charset_utf8,
             qop,
             nc: 0,
+            max_nc: u32::MAX,
+            cnonce: [0; 16],
+            cnonce_len: 16,
+            precomputed: None,
         })
     }
 }
@@ -482,11 +1166,21 @@ impl std::fmt::Debug for DigestClient {
             .field("opaque", &self.opaque())
             .field("nonce", &self.nonce())
             .field("algorithm", &self.algorithm.as_str(self.session))
+            .field("algorithm_explicit", &self.algorithm_explicit)
             .field("stale", &self.stale)
             .field("qop", &self.qop)
             .field("rfc2069_compat", &self.rfc2069_compat)
             .field("userhash", &self.userhash)
+            .field("charset", &self.charset())
             .field("nc", &self.nc)
+            .field("max_nc", &self.max_nc)
+            .field(
+                "precomputed_username",
+                &self
+                    .precomputed
+                    .as_ref()
+                    .and_then(|c| c.username.as_deref()),
+            )
             .finish()
     }
 }
@@ -533,6 +1227,60 @@ fn append_extended_key_value(out: &mut String, key: &str, value: &str) {
     out.push_str(", ");
 }
 
+/// Decodes a `username*` parameter value, as sent by a client whose username
+/// can't be represented in a plain `username`.
+///
+/// The value uses the `ext-value` encoding of [RFC 8187 section
+/// 3.2](https://datatracker.ietf.org/doc/html/rfc8187#section-3.2): a
+/// charset, an optional language tag, and the percent-encoded username. Only
+/// the `UTF-8` charset is supported, as required by [RFC 7616 section
+/// 3.4](https://datatracker.ietf.org/doc/html/rfc7616#section-3.4).
+///
+/// ```rust
+/// use http_auth::digest::decode_username_star;
+/// assert_eq!(
+///     decode_username_star("UTF-8''J%C3%A4s%C3%B8n%20Doe").unwrap(),
+///     "J\u{e4}s\u{f8}n Doe",
+/// );
+/// decode_username_star("ISO-8859-1''J%E4s%F8n").unwrap_err();
+/// ```
+pub fn decode_username_star(value: &str) -> Result<String, String> {
+    let mut parts = value.splitn(3, '\'');
+    let (charset, language, encoded) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(c), Some(l), Some(e)) => (c, l, e),
+        _ => return Err(format!("username* value {:?} lacks charset", value)),
+    };
+    if !charset.eq_ignore_ascii_case("UTF-8") {
+        return Err(format!("unsupported username* charset {:?}", charset));
+    }
+    if !language
+        .bytes()
+        .all(|b| b.is_ascii_alphanumeric() || b == b'-')
+    {
+        return Err(format!("invalid username* language {:?}", language));
+    }
+    let mut decoded = Vec::with_capacity(encoded.len());
+    let mut bytes = encoded.bytes();
+    while let Some(b) = bytes.next() {
+        if b == b'%' {
+            let hex = |b: Option<u8>| b.and_then(|b| char::from(b).to_digit(16));
+            match (hex(bytes.next()), hex(bytes.next())) {
+                (Some(hi), Some(lo)) => decoded.push((hi << 4 | lo) as u8),
+                _ => return Err(format!("invalid percent-encoding in username* {:?}", value)),
+            }
+        } else if (char_classes(b) & C_ATTR) != 0 {
+            decoded.push(b);
+        } else {
+            return Err(format!(
+                "invalid byte {:?} in username* {:?}",
+                char::from(b),
+                value
+            ));
+        }
+    }
+    String::from_utf8(decoded).map_err(|_| format!("username* {:?} isn't valid UTF-8", value))
+}
+
 #[inline(never)]
 fn append_unquoted_key_value(out: &mut String, key: &str, value: &str) {
     out.push_str(key);
@@ -545,28 +1293,86 @@ fn append_unquoted_key_value(out: &mut String, key: &str, value: &str) {
 fn append_quoted_key_value(out: &mut String, key: &str, value: &str) -> Result<(), String> {
     out.push_str(key);
     out.push_str("=\"");
-    let mut first_unwritten = 0;
-    let bytes = value.as_bytes();
-    for (i, &b) in bytes.iter().enumerate() {
-        // Note that bytes >= 128 are in neither C_QDTEXT nor C_ESCAPABLE, so every allowed byte
-        // is a full UTF-8 code point.
-        let class = char_classes(b);
-        if (class & C_QDTEXT) != 0 {
-            // Just advance.
-        } else if (class & C_ESCAPABLE) != 0 {
-            out.push_str(&value[first_unwritten..i]);
-            out.push('\\');
-            out.push(char::from(b));
-            first_unwritten = i + 1;
-        } else {
-            return Err(format!("invalid {} value {:?}", key, value));
-        }
+    if append_escaped(value, out).is_none() {
+        return Err(format!("invalid {} value {:?}", key, value));
     }
-    out.push_str(&value[first_unwritten..]);
     out.push_str("\", ");
     Ok(())
 }
 
+/// Parsed `Authentication-Info` or `Proxy-Authentication-Info` header value,
+/// as in [RFC 7616 section
+/// 3.5](https://datatracker.ietf.org/doc/html/rfc7616#section-3.5).
+///
+/// All fields are optional; the server may send any subset. Unrecognized
+/// parameters are ignored. So are malformed or repeated values of fields other
+/// than `nextnonce`, keeping the first valid value, so that a quirk in an
+/// informational field doesn't prevent following `nextnonce`. A repeated
+/// `nextnonce` is an error, as it's unclear which to use.
+///
+/// ```rust
+/// use http_auth::digest::{AuthenticationInfo, Qop};
+/// let info: AuthenticationInfo = r#"nextnonce="abc", qop=auth, nc=0000000a"#
+///     .parse()
+///     .unwrap();
+/// assert_eq!(info.nextnonce.as_deref(), Some("abc"));
+/// assert_eq!(info.qop, Some(Qop::Auth));
+/// assert_eq!(info.nc, Some(10));
+/// assert_eq!(info.rspauth, None);
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct AuthenticationInfo {
+    /// The nonce the server wants the client to use for a future request.
+    pub nextnonce: Option<String>,
+
+    /// The quality of protection applied to the response.
+    pub qop: Option<Qop>,
+
+    /// The response auth, which proves the server knows the user's secret.
+    pub rspauth: Option<String>,
+
+    /// The client nonce, echoed from the request.
+    pub cnonce: Option<String>,
+
+    /// The nonce count, echoed from the request.
+    pub nc: Option<u32>,
+}
+
+impl std::str::FromStr for AuthenticationInfo {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut info = AuthenticationInfo::default();
+        for (k, v) in crate::parser::parse_params(s).map_err(|e| e.to_string())? {
+            /// Sets `field` to `v` unless it's already set or `v` is invalid.
+            fn set_lenient<T>(field: &mut Option<T>, v: Option<T>) {
+                if field.is_none() {
+                    *field = v;
+                }
+            }
+            if k.eq_ignore_ascii_case("nextnonce") {
+                if info.nextnonce.replace(v.to_unescaped()).is_some() {
+                    return Err(format!("duplicate parameter {:?}", k));
+                }
+            } else if k.eq_ignore_ascii_case("qop") {
+                set_lenient(&mut info.qop, v.to_unescaped().parse().ok());
+            } else if k.eq_ignore_ascii_case("rspauth") {
+                set_lenient(&mut info.rspauth, Some(v.to_unescaped()));
+            } else if k.eq_ignore_ascii_case("cnonce") {
+                set_lenient(&mut info.cnonce, Some(v.to_unescaped()));
+            } else if k.eq_ignore_ascii_case("nc") {
+                let nc = match v.escaped.len() {
+                    8 => u32::from_str_radix(v.escaped, 16).ok(),
+                    _ => None,
+                };
+                set_lenient(&mut info.nc, nc);
+            }
+        }
+        Ok(info)
+    }
+}
+
 /// Supported algorithm from the [HTTP Digest Algorithm Values
 /// registry](https://www.iana.org/assignments/http-dig-alg/http-dig-alg.xhtml).
 ///
@@ -607,6 +1413,17 @@ impl Algorithm {
         }
     }
 
+    /// Returns `H("")`, which `auth-int` uses for an empty body.
+    fn h_empty(&self) -> &'static str {
+        match self {
+            Algorithm::Md5 => "d41d8cd98f00b204e9800998ecf8427e",
+            Algorithm::Sha256 => "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            Algorithm::Sha512Trunc256 => {
+                "c672b8d1ef56ed28ab87c3622c5114069bdd3ad7b8f9737498d0c01ecef0967a"
+            }
+        }
+    }
+
     #[inline(never)]
     fn h(&self, items: &[&[u8]]) -> String {
         match self {
@@ -617,6 +1434,31 @@ impl Algorithm {
     }
 }
 
+/// Computes `H(A1)` for the non-session variant of `algorithm`, as lowercase
+/// hex, as in [RFC 7616 section
+/// 3.4.2](https://datatracker.ietf.org/doc/html/rfc7616#section-3.4.2).
+///
+/// This is the value stored in `.htdigest` files, and can be supplied to
+/// [`DigestClient::with_precomputed`] or [`DigestClient::set_ha1`] in place
+/// of the password.
+///
+/// ```rust
+/// use http_auth::digest::{compute_ha1, Algorithm};
+/// assert_eq!(
+///     compute_ha1("Mufasa", "http-auth@example.org", "Circle of Life", Algorithm::Md5),
+///     "3d78807defe7de2157e2b0b6573a855f",
+/// );
+/// ```
+pub fn compute_ha1(username: &str, realm: &str, password: &str, algorithm: Algorithm) -> String {
+    algorithm.h(&[
+        username.as_bytes(),
+        b":",
+        realm.as_bytes(),
+        b":",
+        password.as_bytes(),
+    ])
+}
+
 fn h<D: Digest>(mut d: D, items: &[&[u8]]) -> String {
     for i in items {
         d.update(i);
@@ -624,11 +1466,6 @@ fn h<D: Digest>(mut d: D, items: &[&[u8]]) -> String {
     hex::encode(d.finalize())
 }
 
-fn new_random_cnonce() -> String {
-    let raw: [u8; 16] = rand::random();
-    hex::encode(&raw[..])
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -866,6 +1703,41 @@ mod tests {
         assert_eq!(ctxs[0].nc, 1);
     }
 
+    #[test]
+    fn decode_username_star() {
+        for username in [
+            "J\u{e4}s\u{f8}n Doe",
+            "\u{5c71}\u{7530}",
+            "\u{1f600}!",
+            "plain",
+        ] {
+            let mut out = String::new();
+            append_extended_key_value(&mut out, "username", username);
+            let value = out
+                .strip_prefix("username*=")
+                .and_then(|v| v.strip_suffix(", "))
+                .unwrap();
+            assert_eq!(super::decode_username_star(value).unwrap(), username);
+        }
+        assert_eq!(
+            super::decode_username_star("utf-8'ja'%E5%B1%B1%e7%94%b0").unwrap(),
+            "\u{5c71}\u{7530}"
+        );
+        for bad in [
+            "J%C3%A4s",
+            "ISO-8859-1''J%E4s",
+            "UTF-8''J%E4s",
+            "UTF-8''J%C3",
+            "UTF-8''J%C",
+            "UTF-8''J%zz",
+            "UTF-8''J s",
+            "UTF-8''\u{e4}",
+            "UTF-8'e n'J",
+        ] {
+            super::decode_username_star(bad).unwrap_err();
+        }
+    }
+
     #[test]
     fn rfc2069() {
         // https://datatracker.ietf.org/doc/html/rfc2069#section-2.4
@@ -905,6 +1777,799 @@ mod tests {
         assert_eq!(ctxs[0].nc, 1);
     }
 
+    /// Tests `H(A1)` for the examples in [RFC 7616 section
+    /// 3.9](https://datatracker.ietf.org/doc/html/rfc7616#section-3.9).
+    #[test]
+    fn compute_ha1() {
+        let (u, r, p) = ("Mufasa", "http-auth@example.org", "Circle of Life");
+        assert_eq!(
+            super::compute_ha1(u, r, p, Algorithm::Md5),
+            "3d78807defe7de2157e2b0b6573a855f"
+        );
+        assert_eq!(
+            super::compute_ha1(u, r, p, Algorithm::Sha256),
+            "7987c64c30e25f1b74be53f966b49b90f2808aa92faf9a00262392d7b4794232"
+        );
+        assert_eq!(
+            super::compute_ha1(
+                "J\u{e4}s\u{f8}n Doe",
+                "api@example.org",
+                "Secret, or not?",
+                Algorithm::Sha512Trunc256
+            ),
+            "2d3d9f12c9f3d30011259dc5fecee005ae24de40e3e1f61806d03e65f1e6024f"
+        );
+    }
+
+    #[test]
+    fn empty_realm() {
+        let challenges = crate::parse_challenges(r#"Digest realm="", nonce="abc""#).unwrap();
+        let mut ctx = DigestClient::try_from(&challenges[0]).unwrap();
+        assert_eq!(ctx.realm(), "");
+        let params = crate::PasswordParams {
+            username: "Mufasa",
+            password: "CircleOfLife",
+            uri: "/dir/index.html",
+            body: None,
+            method: "GET",
+        };
+        assert_eq!(
+            ctx.respond_with_testing_cnonce(&params, "unused").unwrap(),
+            "\
+            Digest \
+            username=\"Mufasa\", \
+            realm=\"\", \
+            uri=\"/dir/index.html\", \
+            nonce=\"abc\", \
+            response=\"54cae92cadba5eb104bcbde29f832833\"",
+        );
+    }
+
+    #[test]
+    fn empty_qop() {
+        let p = crate::PasswordParams::new("Mufasa", "CircleOfLife")
+            .with_uri("/dir/index.html")
+            .with_method("GET");
+        let mut responses = Vec::new();
+        for qop in [None, Some(""), Some(" ")] {
+            let mut params = vec![
+                ("realm", "testrealm@host.com"),
+                ("nonce", "dcd98b7102dd2f0e8b11d0f600bfb0c093"),
+            ];
+            params.extend(qop.map(|q| ("qop", q)));
+            let mut ctx = DigestClient::from_params(&params).unwrap();
+            assert!(ctx.rfc2069_compat());
+            responses.push(ctx.respond(&p).unwrap());
+        }
+        assert!(
+            responses[0].contains("response=\"1949323746fe6a43ef61f9606e7febea\""),
+            "{}",
+            responses[0]
+        );
+        assert!(!responses[0].contains("qop="), "{}", responses[0]);
+        assert_eq!(responses[0], responses[1]);
+        assert_eq!(responses[0], responses[2]);
+    }
+
+    #[test]
+    fn supports_qop() {
+        for (qop, auth, auth_int) in [
+            (Some("auth"), true, false),
+            (Some("auth-int"), false, true),
+            (Some("auth, auth-int"), true, true),
+            (None, true, false),
+        ] {
+            let mut params = vec![("realm", "r"), ("nonce", "n")];
+            params.extend(qop.map(|q| ("qop", q)));
+            let ctx = DigestClient::from_params(&params).unwrap();
+            assert_eq!(ctx.supports_qop(Qop::Auth), auth, "{:?}", qop);
+            assert_eq!(ctx.supports_qop(Qop::AuthInt), auth_int, "{:?}", qop);
+        }
+    }
+
+    #[test]
+    fn respond_into() {
+        let mut ctx =
+            DigestClient::from_params(&[("realm", "r"), ("nonce", "n"), ("qop", "auth")]).unwrap();
+        let mut out = "prefix: ".to_owned();
+        let p = crate::PasswordParams::new("u", "p")
+            .with_uri("/\n")
+            .with_method("GET");
+        ctx.respond_into(&p, &mut out).unwrap_err();
+        assert_eq!(out, "prefix: ");
+        assert_eq!(ctx.nonce_count(), 0);
+        let p = p.with_uri("/");
+        ctx.respond_into(&p, &mut out).unwrap();
+        assert!(out.starts_with("prefix: Digest "), "{}", out);
+        assert!(out.contains("nc=00000001"), "{}", out);
+    }
+
+    #[test]
+    fn set_ha1() {
+        let cnonce = "f2/wE4q74E6zIJEtWaHKaf5wv/H5QzzpXusqGemxURZJ";
+        for algorithm in ["MD5", "MD5-sess"] {
+            let ctx = DigestClient::from_params(&[
+                ("realm", "http-auth@example.org"),
+                ("nonce", "7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v"),
+                ("qop", "auth"),
+                ("algorithm", algorithm),
+            ])
+            .unwrap();
+            let p = crate::PasswordParams::new("Mufasa", "Circle of Life")
+                .with_uri("/dir/index.html")
+                .with_method("GET");
+            let mut expected = String::new();
+            ctx.clone()
+                .respond_inner(&p, cnonce, &mut expected)
+                .unwrap();
+            let p = crate::PasswordParams::new("Mufasa", "")
+                .with_uri("/dir/index.html")
+                .with_method("GET");
+            for ha1 in [
+                "3d78807defe7de2157e2b0b6573a855f",
+                "3D78807DEFE7DE2157E2B0B6573A855F",
+            ] {
+                let mut ctx = ctx.clone();
+                ctx.set_ha1(ha1).unwrap();
+                let mut out = String::new();
+                ctx.respond_inner(&p, cnonce, &mut out).unwrap();
+                assert_eq!(out, expected, "{} {}", algorithm, ha1);
+            }
+            for ha1 in [
+                "",
+                "3d78807defe7de2157e2b0b6573a855",
+                "3d78807defe7de2157e2b0b6573a855g",
+            ] {
+                let mut ctx = ctx.clone();
+                let e = ctx.set_ha1(ha1).unwrap_err();
+                assert_eq!(e, "H(A1) for MD5 should be 32 hex digits");
+                assert!(ctx.precomputed.is_none());
+            }
+        }
+    }
+
+    #[test]
+    fn with_precomputed() {
+        let input = "Digest \
+                     realm=\"http-auth@example.org\", \
+                     qop=\"auth, auth-int\", \
+                     algorithm=MD5, \
+                     nonce=\"7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v\", \
+                     opaque=\"FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS\"";
+        let c = &crate::parse_challenges(input).unwrap()[0];
+        DigestClient::with_precomputed(c, "Mufasa", "3d78").unwrap_err();
+        let mut ctx =
+            DigestClient::with_precomputed(c, "Mufasa", "3D78807DEFE7DE2157E2B0B6573A855F")
+                .unwrap();
+        assert!(!format!("{:?}", ctx).contains("3d78"));
+        let p = crate::PasswordParams::new("ignored", "ignored")
+            .with_uri("/dir/index.html")
+            .with_method("GET");
+        let out = ctx
+            .respond_with_testing_cnonce(&p, "f2/wE4q74E6zIJEtWaHKaf5wv/H5QzzpXusqGemxURZJ")
+            .unwrap();
+        assert!(out.starts_with("Digest username=\"Mufasa\", "), "{}", out);
+        assert!(
+            out.contains("response=\"8ca523f5e9506fed4657c9700eebdbec\""),
+            "{}",
+            out
+        );
+        ctx.response_digest(&p).unwrap();
+        assert_eq!(ctx.nonce_count(), 2);
+    }
+
+    #[test]
+    fn cnonce_len() {
+        let mut ctx =
+            DigestClient::from_params(&[("realm", "r"), ("nonce", "n"), ("qop", "auth")]).unwrap();
+        assert_eq!(ctx.cnonce_len(), 16);
+        ctx.set_cnonce_len(7).unwrap_err();
+        ctx.set_cnonce_len(17).unwrap_err();
+        ctx.set_cnonce_len(10).unwrap();
+        let p = crate::PasswordParams::new("u", "p")
+            .with_uri("/")
+            .with_method("GET");
+        let out = ctx.respond(&p).unwrap();
+        let cnonce = ctx.client_nonce().unwrap();
+        assert_eq!(cnonce.len(), 20);
+        assert!(out.contains(&format!("cnonce=\"{}\"", cnonce)), "{}", out);
+        ctx.set_cnonce_len(10).unwrap();
+        ctx.set_cnonce_len(16).unwrap_err();
+
+        // The length survives a new nonce, and a session resumes with it.
+        let c = &crate::parse_challenges(r#"Digest realm="r", nonce="m", qop="auth""#).unwrap()[0];
+        assert!(!ctx.update_from_challenge(c).unwrap());
+        assert_eq!(ctx.cnonce_len(), 10);
+        let mut resumed = DigestClient::try_from(c).unwrap();
+        resumed.resume_session(1, &cnonce).unwrap_err();
+        resumed.set_cnonce_len(10).unwrap();
+        resumed.resume_session(1, &cnonce).unwrap();
+        assert_eq!(resumed.client_nonce().unwrap(), cnonce);
+    }
+
+    /// The `nonce` and `opaque` are stored unescaped and re-escaped when
+    /// echoed, so the server sees the same values after its own unescaping,
+    /// even if it escaped characters unnecessarily.
+    #[test]
+    fn echo_escaped() {
+        let input = r#"Digest realm="r", nonce="a\"b\\c\d", opaque="\"o\"", qop=auth"#;
+        let c = &crate::parse_challenges(input).unwrap()[0];
+        let mut ctx = DigestClient::try_from(c).unwrap();
+        assert_eq!(ctx.nonce(), r#"a"b\cd"#);
+        let p = crate::PasswordParams::new("u", "p")
+            .with_uri("/")
+            .with_method("GET");
+        let out = ctx.respond_with_testing_cnonce(&p, "c").unwrap();
+        assert!(out.contains(r#" nonce="a\"b\\cd", "#), "{}", out);
+        assert!(out.contains(r#" opaque="\"o\"""#), "{}", out);
+
+        // Parse the response as the server would.
+        let echoed = &crate::parse_challenges(&out).unwrap()[0];
+        let param = |name| {
+            echoed
+                .params
+                .iter()
+                .find(|(k, _)| *k == name)
+                .unwrap()
+                .1
+                .to_unescaped()
+        };
+        assert_eq!(param("nonce"), r#"a"b\cd"#);
+        assert_eq!(param("opaque"), r#""o""#);
+
+        // The digest is over the unescaped nonce.
+        let mut unescaped = DigestClient::from_params(&[
+            ("realm", "r"),
+            ("nonce", r#"a"b\cd"#),
+            ("opaque", r#""o""#),
+            ("qop", "auth"),
+        ])
+        .unwrap();
+        assert_eq!(unescaped.respond_with_testing_cnonce(&p, "c").unwrap(), out);
+    }
+
+    #[test]
+    fn strict() {
+        let input = r#"Digest realm="r", nonce="n", qop="auth", stale=false, foo=bar"#;
+        let c = &crate::parse_challenges(input).unwrap()[0];
+        DigestClient::try_from(c).unwrap();
+        let e = DigestClient::try_from_strict(c).unwrap_err();
+        assert_eq!(e, "unexpected parameter \"foo\"");
+        let input = "Digest realm=\"r\", domain=\"/\", nonce=\"n\", opaque=\"o\", \
+                     stale=false, algorithm=MD5, qop=\"auth\", charset=UTF-8, userhash=false";
+        let c = &crate::parse_challenges(input).unwrap()[0];
+        DigestClient::try_from_strict(c).unwrap();
+    }
+
+    #[test]
+    fn from_params() {
+        let parsed = DigestClient::try_from(
+            &crate::parse_challenges(
+                "Digest \
+                 realm=\"a \\\"quoted\\\" realm\", \
+                 qop=\"auth, auth-int\", \
+                 algorithm=SHA-256, \
+                 nonce=\"7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v\", \
+                 opaque=\"FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS\"",
+            )
+            .unwrap()[0],
+        )
+        .unwrap();
+        let from_params = DigestClient::from_params(&[
+            ("realm", "a \"quoted\" realm"),
+            ("qop", "auth, auth-int"),
+            ("algorithm", "SHA-256"),
+            ("nonce", "7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v"),
+            ("opaque", "FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS"),
+        ])
+        .unwrap();
+        assert_eq!(parsed, from_params);
+        DigestClient::from_params(&[("realm", "foo")]).unwrap_err(); // missing nonce
+    }
+
+    #[test]
+    fn missing_realm() {
+        use std::convert::TryFrom as _;
+        let e = crate::PasswordClient::try_from(r#"Digest nonce="x""#).unwrap_err();
+        assert_eq!(e, "missing required parameter realm");
+    }
+
+    #[test]
+    fn algorithm_default() {
+        let nonce = ("nonce", "7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v");
+        let ctx = DigestClient::from_params(&[("realm", "foo"), nonce]).unwrap();
+        assert_eq!(ctx.algorithm(), Algorithm::Md5);
+        assert!(!ctx.algorithm_was_explicit());
+
+        let ctx =
+            DigestClient::from_params(&[("realm", "foo"), ("algorithm", "MD5"), nonce]).unwrap();
+        assert_eq!(ctx.algorithm(), Algorithm::Md5);
+        assert!(ctx.algorithm_was_explicit());
+
+        let ctx = DigestClient::from_params(&[("realm", "foo"), ("algorithm", "SHA-256"), nonce])
+            .unwrap();
+        assert_eq!(ctx.algorithm(), Algorithm::Sha256);
+        assert!(ctx.algorithm_was_explicit());
+    }
+
+    #[test]
+    fn stable_cnonce() {
+        let mut ctx = DigestClient::from_params(&[
+            ("realm", "http-auth@example.org"),
+            ("qop", "auth"),
+            ("nonce", "7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v"),
+        ])
+        .unwrap();
+        let params = crate::PasswordParams {
+            username: "Mufasa",
+            password: "Circle of Life",
+            uri: "/dir/index.html",
+            body: None,
+            method: "GET",
+        };
+        let get = |response: &str, key: &str| -> String {
+            let c = &crate::parse_challenges(response).unwrap()[0];
+            let v = c.params.iter().find(|(k, _)| *k == key).unwrap().1;
+            v.to_unescaped()
+        };
+        let first = ctx.respond(&params).unwrap();
+        let second = ctx.respond(&params).unwrap();
+        assert_eq!(get(&first, "cnonce"), get(&second, "cnonce"));
+        assert_eq!(get(&first, "nc"), "00000001");
+        assert_eq!(get(&second, "nc"), "00000002");
+        assert_ne!(get(&first, "response"), get(&second, "response"));
+
+        ctx.reset_nonce_count();
+        assert_eq!(ctx.nonce_count(), 0);
+        let third = ctx.respond(&params).unwrap();
+        assert_eq!(get(&third, "nc"), "00000001");
+        assert_ne!(get(&first, "cnonce"), get(&third, "cnonce"));
+    }
+
+    #[test]
+    fn auth_info() {
+        let mut ctx = DigestClient::from_params(&[
+            ("realm", "r"),
+            ("qop", "auth"),
+            ("opaque", "o"),
+            ("nonce", "a"),
+        ])
+        .unwrap();
+        ctx.respond(&crate::PasswordParams {
+            username: "u",
+            password: "p",
+            uri: "/",
+            body: None,
+            method: "GET",
+        })
+        .unwrap();
+        ctx.process_auth_info(r#"qop=auth, rspauth="x""#).unwrap();
+        assert_eq!(ctx.nonce(), "a");
+        assert_eq!(ctx.nonce_count(), 1);
+        ctx.process_auth_info(r#"qop=auth, nextnonce="b\"c""#)
+            .unwrap();
+        assert_eq!(ctx.nonce(), "b\"c");
+        assert_eq!(ctx.opaque(), Some("o"));
+        assert_eq!(ctx.realm(), "r");
+        assert_eq!(ctx.nonce_count(), 0);
+        ctx.process_auth_info("Digest nextnonce=c").unwrap_err();
+        assert_eq!(ctx.nonce(), "b\"c");
+
+        // Malformed informational fields don't prevent following nextnonce.
+        ctx.process_auth_info(r#"qop=bogus, nc=1, cnonce=x, cnonce=y, nextnonce="d""#)
+            .unwrap();
+        assert_eq!(ctx.nonce(), "d");
+    }
+
+    #[test]
+    fn set_nonce_count() {
+        let mut ctx =
+            DigestClient::from_params(&[("realm", "r"), ("nonce", "n"), ("qop", "auth")]).unwrap();
+        let params = crate::PasswordParams {
+            username: "u",
+            password: "p",
+            uri: "/",
+            body: None,
+            method: "GET",
+        };
+        ctx.set_nonce_count(5).unwrap();
+        assert_eq!(ctx.nonce_count(), 5);
+        assert_ne!(ctx.cnonce, [0; 16]);
+        ctx.set_nonce_count(5).unwrap();
+        ctx.set_nonce_count(4).unwrap_err();
+        assert_eq!(ctx.nonce_count(), 5);
+        let response = ctx.respond(&params).unwrap();
+        assert!(response.contains("nc=00000006"), "{}", response);
+    }
+
+    /// Tests that skipping ahead mid-session keeps the client nonce, and so
+    /// the session `H(A1)`.
+    #[test]
+    fn set_nonce_count_sess() {
+        let challenge = [
+            ("realm", "r"),
+            ("nonce", "n"),
+            ("qop", "auth"),
+            ("algorithm", "MD5-sess"),
+        ];
+        let params = crate::PasswordParams::new("u", "p")
+            .with_uri("/")
+            .with_method("GET");
+        let mut ctx = DigestClient::from_params(&challenge).unwrap();
+        ctx.respond(&params).unwrap();
+        let cnonce = ctx.client_nonce().unwrap();
+        ctx.set_nonce_count(5).unwrap();
+        assert_eq!(ctx.client_nonce().unwrap(), cnonce);
+        let response = ctx.respond(&params).unwrap();
+        assert!(response.contains("nc=00000006"), "{}", response);
+        assert!(
+            response.contains(&format!("cnonce=\"{}\"", cnonce)),
+            "{}",
+            response
+        );
+
+        // The response matches one computed from the original session.
+        let mut resumed = DigestClient::from_params(&challenge).unwrap();
+        resumed.resume_session(5, &cnonce).unwrap();
+        assert_eq!(resumed.respond(&params).unwrap(), response);
+    }
+
+    #[test]
+    fn resume_session() {
+        let params = [
+            ("realm", "r"),
+            ("nonce", "n"),
+            ("qop", "auth"),
+            ("algorithm", "MD5-sess"),
+        ];
+        let p = crate::PasswordParams {
+            username: "u",
+            password: "p",
+            uri: "/",
+            body: None,
+            method: "GET",
+        };
+        let mut original = DigestClient::from_params(&params).unwrap();
+        assert_eq!(original.client_nonce(), None);
+        original.respond(&p).unwrap();
+        original.respond(&p).unwrap();
+        let cnonce = original.client_nonce().unwrap();
+
+        let mut restored = DigestClient::from_params(&params).unwrap();
+        restored
+            .resume_session(original.nonce_count(), &cnonce)
+            .unwrap();
+        assert!(restored.session());
+        assert_eq!(restored.respond(&p).unwrap(), original.respond(&p).unwrap());
+        assert_eq!(restored.respond(&p).unwrap(), original.respond(&p).unwrap());
+
+        restored.resume_session(1, &cnonce).unwrap_err();
+        restored.resume_session(10, "xyz").unwrap_err();
+        restored.resume_session(0, &cnonce).unwrap_err();
+    }
+
+    #[test]
+    fn h_empty() {
+        for a in [Algorithm::Md5, Algorithm::Sha256, Algorithm::Sha512Trunc256] {
+            assert_eq!(a.h_empty(), a.h(&[]), "{:?}", a);
+        }
+    }
+
+    /// Tests `auth-int`, with expected values calculated independently.
+    #[test]
+    fn auth_int() {
+        let mut ctx =
+            DigestClient::from_params(&[("realm", "r"), ("nonce", "n"), ("qop", "auth-int")])
+                .unwrap();
+        let mut p = crate::PasswordParams {
+            username: "u",
+            password: "p",
+            uri: "/",
+            body: Some(&[]),
+            method: "POST",
+        };
+        let empty = ctx.respond_with_testing_cnonce(&p, "c").unwrap();
+        assert!(empty.contains("qop=auth-int"), "{}", empty);
+        assert!(
+            empty.contains(r#"response="bc3f847cc40fe2ff7c4782d8fb133597""#),
+            "{}",
+            empty
+        );
+
+        ctx.reset_nonce_count();
+        p.body = Some(b"x");
+        let x = ctx.respond_with_testing_cnonce(&p, "c").unwrap();
+        assert!(
+            x.contains(r#"response="65803d91420512fa27c57421cc51705f""#),
+            "{}",
+            x
+        );
+
+        // Without the body, auth-int is unusable, and the server doesn't offer auth.
+        p.body = None;
+        ctx.respond_with_testing_cnonce(&p, "c").unwrap_err();
+    }
+
+    /// Parses an `Authentication-Info` header for the request of [RFC 7616
+    /// section 3.9.1](https://datatracker.ietf.org/doc/html/rfc7616#section-3.9.1).
+    /// The RFC doesn't include one, so `rspauth` is a placeholder.
+    #[test]
+    fn authentication_info() {
+        let info: AuthenticationInfo = "qop=auth, \
+                rspauth=\"6629fae49393a05397450978507c4ef1\", \
+                cnonce=\"f2/wE4q74E6zIJEtWaHKaf5wv/H5QzzpXusqGemxURZJ\", \
+                nc=00000001, \
+                nextnonce=\"ExPq\\\"4\""
+            .parse()
+            .unwrap();
+        assert_eq!(
+            info,
+            AuthenticationInfo {
+                nextnonce: Some("ExPq\"4".to_owned()),
+                qop: Some(Qop::Auth),
+                rspauth: Some("6629fae49393a05397450978507c4ef1".to_owned()),
+                cnonce: Some("f2/wE4q74E6zIJEtWaHKaf5wv/H5QzzpXusqGemxURZJ".to_owned()),
+                nc: Some(1),
+            }
+        );
+        assert_eq!(
+            "".parse::<AuthenticationInfo>().unwrap(),
+            AuthenticationInfo::default()
+        );
+        "nextnonce=a, nextnonce=b"
+            .parse::<AuthenticationInfo>()
+            .unwrap_err();
+
+        // Other fields are lenient.
+        assert_eq!(
+            "QOP=AUTH-INT, qop=auth, nc=1, nc=00000002, rspauth=a, rspauth=b"
+                .parse::<AuthenticationInfo>()
+                .unwrap(),
+            AuthenticationInfo {
+                qop: Some(Qop::AuthInt),
+                rspauth: Some("a".to_owned()),
+                nc: Some(2),
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            "qop=auth-conf, nextnonce=x"
+                .parse::<AuthenticationInfo>()
+                .unwrap(),
+            AuthenticationInfo {
+                nextnonce: Some("x".to_owned()),
+                ..Default::default()
+            }
+        );
+    }
+
+    /// Checks `response_digest` against the MD5 response of [RFC 7616 section
+    /// 3.9.1](https://datatracker.ietf.org/doc/html/rfc7616#section-3.9.1).
+    #[test]
+    fn response_digest() {
+        let params = [
+            ("realm", "http-auth@example.org"),
+            ("qop", "auth"),
+            ("algorithm", "MD5"),
+            ("nonce", "7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v"),
+        ];
+        let p = crate::PasswordParams {
+            username: "Mufasa",
+            password: "Circle of Life",
+            uri: "/dir/index.html",
+            body: None,
+            method: "GET",
+        };
+        let mut ctx = DigestClient::from_params(&params).unwrap();
+        let response = ctx.response_digest(&p).unwrap();
+        assert_eq!(ctx.nonce_count(), 1);
+
+        // `respond` with the same cnonce should produce the same digest.
+        let cnonce = ctx.client_nonce().unwrap();
+        let mut other = DigestClient::from_params(&params).unwrap();
+        let full = other.respond_with_testing_cnonce(&p, &cnonce).unwrap();
+        assert!(
+            full.contains(&format!("response=\"{}\"", response)),
+            "{} vs {}",
+            full,
+            response
+        );
+
+        let (_, nc, rfc) = DigestClient::from_params(&params)
+            .unwrap()
+            .compute_response(&p, "f2/wE4q74E6zIJEtWaHKaf5wv/H5QzzpXusqGemxURZJ", None)
+            .unwrap();
+        assert_eq!(nc, 1);
+        assert_eq!(rfc, "8ca523f5e9506fed4657c9700eebdbec");
+    }
+
+    #[test]
+    fn quoted_algorithm() {
+        for algorithm in [r#"SHA-256"#, r#""SHA-256""#, r#""SHA\-256""#] {
+            let c = format!(
+                r#"Digest realm="r", nonce="n", qop="auth", algorithm={}, stale="true""#,
+                algorithm
+            );
+            let c = &crate::parse_challenges(&c).unwrap()[0];
+            let ctx = DigestClient::try_from(c).unwrap();
+            assert_eq!(ctx.algorithm(), Algorithm::Sha256, "{}", algorithm);
+            assert!(ctx.stale());
+        }
+    }
+
+    #[test]
+    fn update_from_challenge() {
+        let challenge = |nonce: &str| {
+            format!(
+                r#"Digest realm="r", nonce="{}", qop="auth", stale=true"#,
+                nonce
+            )
+        };
+        let p = crate::PasswordParams {
+            username: "u",
+            password: "p",
+            uri: "/",
+            body: None,
+            method: "GET",
+        };
+        let first = challenge("a");
+        let mut ctx = DigestClient::try_from(&crate::parse_challenges(&first).unwrap()[0]).unwrap();
+        ctx.set_max_reuse(10);
+        ctx.respond(&p).unwrap();
+        ctx.respond(&p).unwrap();
+        let cnonce = ctx.client_nonce();
+
+        // Same nonce: keep counting.
+        let same = challenge("a");
+        assert!(ctx
+            .update_from_challenge(&crate::parse_challenges(&same).unwrap()[0])
+            .unwrap());
+        assert!(ctx.stale());
+        assert_eq!(ctx.nonce_count(), 2);
+        assert_eq!(ctx.client_nonce(), cnonce);
+        let r = ctx.respond(&p).unwrap();
+        assert!(r.contains("nc=00000003"), "{}", r);
+
+        // New nonce: start over.
+        let new = challenge("b");
+        assert!(!ctx
+            .update_from_challenge(&crate::parse_challenges(&new).unwrap()[0])
+            .unwrap());
+        assert_eq!(ctx.nonce(), "b");
+        assert_eq!(ctx.nonce_count(), 0);
+        assert_eq!(ctx.max_reuse(), 10);
+
+        // New realm: fail without changing anything.
+        let before = ctx.clone();
+        let other = r#"Digest realm="other", nonce="b", qop="auth", stale=true"#;
+        let e = ctx
+            .update_from_challenge(&crate::parse_challenges(other).unwrap()[0])
+            .unwrap_err();
+        assert!(e.contains("realm changed"), "{}", e);
+        assert!(ctx == before);
+    }
+
+    #[test]
+    fn update_from_challenge_precomputed() {
+        let cnonce = "f2/wE4q74E6zIJEtWaHKaf5wv/H5QzzpXusqGemxURZJ";
+        let challenge = |nonce: &str, algorithm: &str| {
+            format!(
+                r#"Digest realm="http-auth@example.org", nonce="{}", qop="auth", algorithm={}, stale=true"#,
+                nonce, algorithm
+            )
+        };
+        let first = challenge("a", "MD5");
+        let mut ctx = DigestClient::with_precomputed(
+            &crate::parse_challenges(&first).unwrap()[0],
+            "Mufasa",
+            "3d78807defe7de2157e2b0b6573a855f",
+        )
+        .unwrap();
+        let p = crate::PasswordParams::new("", "")
+            .with_uri("/dir/index.html")
+            .with_method("GET");
+        ctx.respond(&p).unwrap();
+
+        // A new nonce, even with the session variant, still uses the stored
+        // credentials.
+        for (nonce, algorithm) in [("b", "MD5"), ("c", "MD5-sess")] {
+            let next = challenge(nonce, algorithm);
+            let next = &crate::parse_challenges(&next).unwrap()[0];
+            assert!(!ctx.update_from_challenge(next).unwrap());
+            let mut expected = DigestClient::try_from(next).unwrap();
+            let expected = expected
+                .respond_with_testing_cnonce(
+                    &crate::PasswordParams::new("Mufasa", "Circle of Life")
+                        .with_uri("/dir/index.html")
+                        .with_method("GET"),
+                    cnonce,
+                )
+                .unwrap();
+            assert_eq!(
+                ctx.clone().respond_with_testing_cnonce(&p, cnonce).unwrap(),
+                expected,
+                "{}",
+                algorithm
+            );
+        }
+
+        // A different algorithm needs a different H(A1).
+        let before = ctx.clone();
+        let other = challenge("d", "SHA-256");
+        let e = ctx
+            .update_from_challenge(&crate::parse_challenges(&other).unwrap()[0])
+            .unwrap_err();
+        assert!(e.contains("algorithm changed"), "{}", e);
+        assert!(ctx == before);
+
+        // Without precomputed credentials, that's fine.
+        let mut ctx = DigestClient::try_from(&crate::parse_challenges(&first).unwrap()[0]).unwrap();
+        ctx.update_from_challenge(&crate::parse_challenges(&other).unwrap()[0])
+            .unwrap();
+        assert_eq!(ctx.algorithm(), Algorithm::Sha256);
+    }
+
+    #[test]
+    fn charset() {
+        let ctx = DigestClient::from_params(&[("realm", "r"), ("nonce", "n")]).unwrap();
+        assert_eq!(ctx.charset(), None);
+        let ctx =
+            DigestClient::from_params(&[("realm", "r"), ("nonce", "n"), ("charset", "utf-8")])
+                .unwrap();
+        assert_eq!(ctx.charset(), Some("UTF-8"));
+        let e =
+            DigestClient::from_params(&[("realm", "r"), ("nonce", "n"), ("charset", "ISO-8859-1")])
+                .unwrap_err();
+        assert!(e.contains("ISO-8859-1"), "{}", e);
+    }
+
+    /// Tests the asterisk-form request target, as in `OPTIONS *`, with the
+    /// expected response calculated independently.
+    #[test]
+    fn asterisk_uri() {
+        let mut ctx =
+            DigestClient::from_params(&[("realm", "r"), ("nonce", "n"), ("qop", "auth")]).unwrap();
+        let p = crate::PasswordParams::new("u", "p")
+            .with_uri("*")
+            .with_method("OPTIONS");
+        let r = ctx.respond_with_testing_cnonce(&p, "c").unwrap();
+        assert!(r.contains(r#"uri="*""#), "{}", r);
+        assert!(
+            r.contains(r#"response="69ff9e2d314ae011015c606823e31138""#),
+            "{}",
+            r
+        );
+    }
+
+    #[test]
+    fn max_reuse() {
+        let mut ctx = DigestClient::from_params(&[
+            ("realm", "http-auth@example.org"),
+            ("qop", "auth"),
+            ("nonce", "7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v"),
+        ])
+        .unwrap();
+        assert_eq!(ctx.max_reuse(), u32::MAX);
+        ctx.set_max_reuse(2);
+        let params = crate::PasswordParams {
+            username: "Mufasa",
+            password: "Circle of Life",
+            uri: "/dir/index.html",
+            body: None,
+            method: "GET",
+        };
+        ctx.respond(&params).unwrap();
+        assert!(!ctx.reuse_exhausted());
+        ctx.respond(&params).unwrap();
+        assert!(ctx.reuse_exhausted());
+        assert_eq!(ctx.respond(&params).unwrap_err(), NONCE_COUNT_EXHAUSTED);
+        assert_eq!(ctx.nonce_count(), 2);
+
+        ctx.set_max_reuse(u32::MAX);
+        ctx.nc = u32::MAX;
+        assert_eq!(ctx.respond(&params).unwrap_err(), NONCE_COUNT_EXHAUSTED);
+    }
+
     // See sizes with: cargo test -- --nocapture digest::tests::size
     #[test]
     fn size() {
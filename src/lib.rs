@@ -18,12 +18,16 @@
 //!
 //! ## Cargo Features
 //!
-//! | feature         | default? | description                                                  |
-//! |-----------------|----------|--------------------------------------------------------------|
-//! | `basic-scheme`  | yes      | support for the `Basic` auth scheme                          |
-//! | `digest-scheme` | yes      | support for the `Digest` auth scheme                         |
-//! | `http`          | no       | convenient conversion from `http` crate types, version 0.2 |
-//! | `http10`        | no       | convenient conversion from `http` crate types, version 1.0 |
+//! | feature            | default? | description                                                |
+//! |--------------------|----------|------------------------------------------------------------|
+//! | `basic-scheme`     | yes      | support for the `Basic` auth scheme                        |
+//! | `bearer-scheme`    | no       | support for the `Bearer` auth scheme                       |
+//! | `digest-scheme`    | yes      | support for the `Digest` auth scheme                       |
+//! | `http`             | no       | convenient conversion from `http` crate types, version 0.2 |
+//! | `http10`           | no       | convenient conversion from `http` crate types, version 1.0 |
+//! | `memchr`           | yes      | faster escape scanning via the `memchr` crate              |
+//! | `negotiate-scheme` | no       | header handling for the `Negotiate` and `NTLM` schemes     |
+//! | `rtsp`             | no       | `RtspAuthenticator` helper for RTSP clients                |
 //!
 //! ## Example
 //!
@@ -82,11 +86,27 @@ pub mod parser;
 #[cfg_attr(docsrs, doc(cfg(feature = "basic-scheme")))]
 pub mod basic;
 
+#[cfg(feature = "bearer-scheme")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bearer-scheme")))]
+pub mod bearer;
+
 #[cfg(feature = "digest-scheme")]
 #[cfg_attr(docsrs, doc(cfg(feature = "digest-scheme")))]
 pub mod digest;
 
-mod table;
+#[cfg(feature = "digest-scheme")]
+#[cfg_attr(docsrs, doc(cfg(feature = "digest-scheme")))]
+pub mod nonce;
+
+#[cfg(feature = "negotiate-scheme")]
+#[cfg_attr(docsrs, doc(cfg(feature = "negotiate-scheme")))]
+pub mod negotiate;
+
+#[cfg(feature = "rtsp")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rtsp")))]
+pub mod rtsp;
+
+pub mod table;
 
 pub use parser::ChallengeParser;
 
@@ -113,7 +133,10 @@ use crate::table::C_ATTR;
 ///
 /// Only supports the param form, not the apocryphal `token68` form, as described
 /// in [`crate::parser::ChallengeParser`].
-#[derive(Clone, Eq, PartialEq)]
+///
+/// Equality and hashing are exact: they compare the scheme case-sensitively
+/// and the params in order, as described at [`ParamValue`].
+#[derive(Clone, Eq, Hash, PartialEq)]
 pub struct ChallengeRef<'i> {
     /// The scheme name, which should be compared case-insensitively.
     pub scheme: &'i str,
@@ -135,6 +158,136 @@ impl<'i> ChallengeRef<'i> {
             params: Vec::new(),
         }
     }
+
+    /// Returns the parameters as unescaped `(name, value)` pairs, in order.
+    ///
+    /// This is intended for diagnostics, logging, or generic key-value
+    /// displays. Clients should use the structured accessors instead.
+    ///
+    /// ```rust
+    /// let c = &http_auth::parse_challenges(r#"Newauth title="Login to \"apps\"""#).unwrap()[0];
+    /// assert_eq!(
+    ///     c.to_unescaped_params(),
+    ///     vec![("title".to_owned(), "Login to \"apps\"".to_owned())],
+    /// );
+    /// ```
+    pub fn to_unescaped_params(&self) -> Vec<(String, String)> {
+        self.params
+            .iter()
+            .map(|(k, v)| ((*k).to_owned(), v.to_unescaped()))
+            .collect()
+    }
+
+    /// Returns the number of parameters.
+    ///
+    /// Together with [`ChallengeRef::param_at`], this allows enumerating
+    /// parameters by index, as is convenient for foreign language bindings.
+    #[inline]
+    pub fn param_count(&self) -> usize {
+        self.params.len()
+    }
+
+    /// Returns the `(name, value)` of the parameter at index `i`, or `None` if
+    /// out of range.
+    ///
+    /// ```rust
+    /// let c = &http_auth::parse_challenges("Basic realm=foo").unwrap()[0];
+    /// assert_eq!(c.param_count(), 1);
+    /// let (k, v) = c.param_at(0).unwrap();
+    /// assert_eq!((k, v.as_escaped()), ("realm", "foo"));
+    /// assert!(c.param_at(1).is_none());
+    /// ```
+    #[inline]
+    pub fn param_at(&self, i: usize) -> Option<(&'i str, &ParamValue<'i>)> {
+        self.params.get(i).map(|(k, v)| (*k, v))
+    }
+
+    /// Creates a [`PasswordClient`] for this challenge, as with
+    /// `PasswordClient::try_from(&challenge)`.
+    ///
+    /// This reads more naturally in iterator chains:
+    ///
+    /// ```rust
+    /// let challenges = http_auth::parse_challenges("Mutual, Basic realm=\"foo\"").unwrap();
+    /// let client = challenges.iter().find_map(|c| c.to_password_client().ok());
+    /// # #[cfg(feature = "basic-scheme")]
+    /// assert_eq!(client.unwrap().realm(), Some("foo"));
+    /// ```
+    #[inline]
+    pub fn to_password_client(&self) -> Result<PasswordClient, String> {
+        PasswordClient::try_from(self)
+    }
+}
+
+/// Parsed challenge which owns its scheme and unescaped parameters.
+///
+/// Unlike [`ChallengeRef`], this doesn't borrow from the header value, so it
+/// can be kept around, such as in a cache of challenges by host. Clients can
+/// be built from it via `PasswordClient::try_from(&owned)`.
+///
+#[cfg_attr(
+    feature = "basic-scheme",
+    doc = r##"
+```rust
+# use std::convert::TryFrom as _;
+use http_auth::{ChallengeOwned, PasswordClient};
+let owned = {
+    let header = String::from(r#"Basic realm="foo""#);
+    ChallengeOwned::from(&http_auth::parse_challenges(&header).unwrap()[0])
+};
+assert_eq!(owned.params, vec![("realm".to_owned(), "foo".to_owned())]);
+let client = PasswordClient::try_from(&owned).unwrap();
+assert_eq!(client.realm(), Some("foo"));
+```
+"##
+)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ChallengeOwned {
+    /// The scheme name, which should be compared case-insensitively.
+    pub scheme: String,
+
+    /// Zero or more parameters as unescaped `(name, value)` pairs, in order.
+    ///
+    /// Conversion from [`ChallengeRef`] preserves the parse order, and
+    /// conversion to a client sees them in this `Vec`'s order.
+    pub params: Vec<(String, String)>,
+}
+
+impl From<&ChallengeRef<'_>> for ChallengeOwned {
+    fn from(c: &ChallengeRef<'_>) -> Self {
+        ChallengeOwned {
+            scheme: c.scheme.to_owned(),
+            params: c.to_unescaped_params(),
+        }
+    }
+}
+
+/// Formats the challenge in header syntax, as accepted by
+/// [`parse_challenges`].
+///
+/// Parameters are written in `params` order, so the output is deterministic.
+/// Each value is written as a token if possible or a quoted string otherwise,
+/// so parsing the output yields an equal `ChallengeRef`. The output may differ
+/// from the original header, for example in whitespace.
+///
+/// ```rust
+/// let header = r#"Digest nonce="abc",realm="a \"b\"", qop=auth"#;
+/// let c = &http_auth::parse_challenges(header).unwrap()[0];
+/// assert_eq!(c.to_string(), r#"Digest nonce=abc, realm="a \"b\"", qop=auth"#);
+/// ```
+impl<'i> std::fmt::Display for ChallengeRef<'i> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.scheme)?;
+        for (i, (k, v)) in self.params.iter().enumerate() {
+            let sep = if i == 0 { " " } else { ", " };
+            if ParamValue::plain(v.escaped).is_ok() {
+                write!(f, "{}{}={}", sep, k, v.escaped)?;
+            } else {
+                write!(f, "{}{}=\"{}\"", sep, k, v.escaped)?;
+            }
+        }
+        Ok(())
+    }
 }
 
 impl<'i> std::fmt::Debug for ChallengeRef<'i> {
@@ -173,15 +326,26 @@ impl<'i> std::fmt::Debug for ParamsPrinter<'i> {
 /// 2.1](https://datatracker.ietf.org/doc/html/rfc7235#section-2.1) advice
 /// for a user-agent to pick the most secure auth-scheme it understands.
 ///
-/// When there are multiple `Digest` challenges, currently uses the first,
-/// consistent with the [RFC 7616 section
-/// 3.7](https://datatracker.ietf.org/doc/html/rfc7616#section-3.7)
-/// advice to "use the first challenge it supports, unless a local policy
-/// dictates otherwise". In the future, it may prioritize by algorithm.
+/// [`PasswordClientBuilder::prefer`] overrides this preference, which may be
+/// useful as a local policy for specific hosts.
+///
+/// When there are multiple `Digest` challenges, prefers `SHA-256` or
+/// `SHA-512-256` over `MD5`, as a local policy under the [RFC 7616 section
+/// 3.7](https://datatracker.ietf.org/doc/html/rfc7616#section-3.7) advice to
+/// "use the first challenge it supports, unless a local policy dictates
+/// otherwise". Among challenges with equally strong algorithms, uses the
+/// first.
 ///
 /// Ignores parse errors as long as there's at least one parseable, supported
 /// challenge.
 ///
+/// Stops considering challenges once it has found one which can't be
+/// bettered, such as a `Digest` challenge with a `SHA-256` algorithm by
+/// default. Later challenges aren't parsed or checked, so they don't appear in
+/// [`PasswordClientBuilder::rejections`] or
+/// [`PasswordClientBuilder::build_all`]. Use
+/// [`PasswordClientBuilder::consider_all`] to examine every challenge.
+///
 /// ## Example
 ///
 #[cfg_attr(
@@ -204,13 +368,150 @@ assert!(matches!(client, PasswordClient::Digest(_)));
 "##
 )]
 #[derive(Default)]
-pub struct PasswordClientBuilder(
-    /// The current result:
-    /// *   `Some(Ok(_))` if there is a suitable client.
-    /// *   `Some(Err(_))` if there is no suitable client and has been a parse error.
-    /// *   `None` otherwise.
-    Option<Result<PasswordClient, String>>,
-);
+pub struct PasswordClientBuilder {
+    /// All suitable clients, in the order their challenges were given.
+    clients: Vec<PasswordClient>,
+
+    /// The first error, reported only if there are no suitable clients.
+    error: Option<String>,
+
+    /// See [`PasswordClientBuilder::rejections`].
+    rejections: Vec<(String, String)>,
+
+    /// The preferred scheme, as set by [`PasswordClientBuilder::prefer`], or
+    /// `None` for the default of `Digest`.
+    prefer: Option<Scheme>,
+
+    /// Schemes to skip, as set by [`PasswordClientBuilder::ignore_scheme`].
+    ignored: Vec<String>,
+
+    /// See [`PasswordClientBuilder::allow_space_separated_params`].
+    space_separated_params: bool,
+
+    /// See [`PasswordClientBuilder::strict_digest_params`].
+    #[cfg(feature = "digest-scheme")]
+    strict_digest_params: bool,
+
+    /// See [`PasswordClientBuilder::on_challenge`].
+    on_challenge: Option<ChallengeCallback>,
+
+    /// See [`PasswordClientBuilder::consider_all`].
+    consider_all: bool,
+
+    /// See [`PasswordClientBuilder::all_challenges`].
+    all_challenges: Vec<ChallengeOwned>,
+}
+
+type ChallengeCallback = Box<dyn FnMut(&ChallengeEvent<'_>) + Send + Sync>;
+
+/// Describes a challenge considered by a [`PasswordClientBuilder`], as passed
+/// to a callback installed via [`PasswordClientBuilder::on_challenge`].
+///
+/// This includes only values from the challenge, never credentials.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct ChallengeEvent<'a> {
+    /// The scheme, as written in the challenge.
+    pub scheme: &'a str,
+
+    /// The `algorithm` parameter, if any, in escaped form without quotes.
+    pub algorithm: Option<&'a str>,
+
+    /// The `qop` parameter, if any, in escaped form without quotes.
+    pub qop: Option<&'a str>,
+
+    /// Why the challenge didn't yield a client, or `None` if it did.
+    pub rejection: Option<&'a str>,
+}
+
+/// An authentication scheme, independent of any client.
+///
+/// Parsing is case-insensitive, as scheme names are. Known schemes are
+/// normalized to their own variants; others are kept as written in
+/// [`Scheme::Other`].
+///
+/// ```rust
+/// use http_auth::Scheme;
+/// assert_eq!("digest".parse::<Scheme>().unwrap(), Scheme::Digest);
+/// assert_eq!(Scheme::Digest.to_string(), "Digest");
+/// assert_eq!(
+///     "Mutual".parse::<Scheme>().unwrap(),
+///     Scheme::Other("Mutual".to_owned()),
+/// );
+/// "not a scheme".parse::<Scheme>().unwrap_err();
+/// ```
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum Scheme {
+    /// The `Basic` scheme, as in [RFC 7617](https://datatracker.ietf.org/doc/html/rfc7617).
+    Basic,
+
+    /// The `Digest` scheme, as in [RFC 7616](https://datatracker.ietf.org/doc/html/rfc7616).
+    Digest,
+
+    /// The `Bearer` scheme, as in [RFC 6750](https://datatracker.ietf.org/doc/html/rfc6750).
+    Bearer,
+
+    /// The `Negotiate` scheme, as in [RFC 4559](https://datatracker.ietf.org/doc/html/rfc4559).
+    Negotiate,
+
+    /// Any other scheme, by name.
+    Other(String),
+}
+
+/// The party a response is directed to.
+///
+/// This determines whether credentials go in an `Authorization` header (in
+/// response to `WWW-Authenticate`) or a `Proxy-Authorization` header (in
+/// response to `Proxy-Authenticate`).
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum AuthTarget {
+    /// The origin server.
+    Origin,
+
+    /// A proxy between the client and the origin server.
+    Proxy,
+}
+
+impl Scheme {
+    /// Returns the scheme's name, as it appears in a challenge.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Scheme::Basic => "Basic",
+            Scheme::Digest => "Digest",
+            Scheme::Bearer => "Bearer",
+            Scheme::Negotiate => "Negotiate",
+            Scheme::Other(s) => s,
+        }
+    }
+}
+
+impl std::str::FromStr for Scheme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() || !s.bytes().all(table::is_tchar) {
+            return Err(format!("invalid scheme {:?}", s));
+        }
+        const KNOWN: [Scheme; 4] = [
+            Scheme::Basic,
+            Scheme::Digest,
+            Scheme::Bearer,
+            Scheme::Negotiate,
+        ];
+        Ok(KNOWN
+            .iter()
+            .find(|k| k.as_str().eq_ignore_ascii_case(s))
+            .cloned()
+            .unwrap_or_else(|| Scheme::Other(s.to_owned())))
+    }
+}
+
+impl std::fmt::Display for Scheme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
 
 /// An error returned by [`HeaderValue::to_str`].
 pub struct ToStrError {
@@ -237,10 +538,101 @@ impl HeaderValue for http10::HeaderValue {
     }
 }
 
+/// A trait for building an `Authorization` or `Proxy-Authorization` header
+/// with http crate 0.2 or 1.0's `HeaderValue` type.
+///
+/// See [`PasswordClient::respond_header`].
+#[cfg(any(feature = "http", feature = "http10"))]
+pub trait AuthorizationHeader: Sized {
+    type Name;
+
+    /// Returns the header name to set for the given target.
+    fn name(target: AuthTarget) -> Self::Name;
+
+    /// Converts a response produced by [`PasswordClient::respond`].
+    fn from_response(value: String) -> Result<Self, String>;
+}
+
+#[cfg(feature = "http")]
+impl AuthorizationHeader for http::HeaderValue {
+    type Name = http::header::HeaderName;
+
+    fn name(target: AuthTarget) -> Self::Name {
+        match target {
+            AuthTarget::Origin => http::header::AUTHORIZATION,
+            AuthTarget::Proxy => http::header::PROXY_AUTHORIZATION,
+        }
+    }
+
+    fn from_response(value: String) -> Result<Self, String> {
+        http::HeaderValue::try_from(value).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(feature = "http10")]
+impl AuthorizationHeader for http10::HeaderValue {
+    type Name = http10::header::HeaderName;
+
+    fn name(target: AuthTarget) -> Self::Name {
+        match target {
+            AuthTarget::Origin => http10::header::AUTHORIZATION,
+            AuthTarget::Proxy => http10::header::PROXY_AUTHORIZATION,
+        }
+    }
+
+    fn from_response(value: String) -> Result<Self, String> {
+        http10::HeaderValue::try_from(value).map_err(|e| e.to_string())
+    }
+}
+
+/// A trait for the parts needed from http crate 0.2 or 1.0's `HeaderMap` type.
+#[cfg(any(feature = "http", feature = "http10"))]
+pub trait HeaderMap {
+    type Value: HeaderValue;
+    type Iter<'a>: Iterator<Item = &'a Self::Value>
+    where
+        Self: 'a;
+
+    /// Returns all `Proxy-Authenticate` values if `proxy`, or all
+    /// `WWW-Authenticate` values otherwise.
+    fn authenticate_values(&self, proxy: bool) -> Self::Iter<'_>;
+}
+
+#[cfg(feature = "http")]
+impl HeaderMap for http::HeaderMap {
+    type Value = http::HeaderValue;
+    type Iter<'a> = http::header::ValueIter<'a, http::HeaderValue>;
+
+    fn authenticate_values(&self, proxy: bool) -> Self::Iter<'_> {
+        let name = if proxy {
+            http::header::PROXY_AUTHENTICATE
+        } else {
+            http::header::WWW_AUTHENTICATE
+        };
+        self.get_all(name).iter()
+    }
+}
+
+#[cfg(feature = "http10")]
+impl HeaderMap for http10::HeaderMap {
+    type Value = http10::HeaderValue;
+    type Iter<'a> = http10::header::ValueIter<'a, http10::HeaderValue>;
+
+    fn authenticate_values(&self, proxy: bool) -> Self::Iter<'_> {
+        let name = if proxy {
+            http10::header::PROXY_AUTHENTICATE
+        } else {
+            http10::header::WWW_AUTHENTICATE
+        };
+        self.get_all(name).iter()
+    }
+}
+
 impl PasswordClientBuilder {
     /// Considers all challenges from the given [`http::HeaderValue`] challenge list.
     #[cfg(any(feature = "http", feature = "http10"))]
     #[cfg_attr(docsrs, doc(cfg(any(feature = "http", feature = "http10"))))]
+    #[must_use]
     pub fn header_value<V: HeaderValue>(mut self, value: &V) -> Self {
         if self.complete() {
             return self;
@@ -248,74 +640,429 @@ impl PasswordClientBuilder {
 
         match value.to_str() {
             Ok(v) => self = self.challenges(v),
-            Err(_) if matches!(self.0, None) => self.0 = Some(Err("non-ASCII header value".into())),
+            Err(_) if self.error.is_none() => self.error = Some("non-ASCII header value".into()),
             _ => {}
         }
 
         self
     }
 
-    /// Returns true if no more challenges need to be examined.
+    /// Prefers the given scheme over others when building.
+    ///
+    /// By default, `Digest` is preferred. When `Basic` is preferred, a
+    /// server offering both will yield a [`BasicClient`].
+    #[must_use]
+    pub fn prefer(mut self, scheme: Scheme) -> Self {
+        self.prefer = Some(scheme);
+        self
+    }
+
+    /// Sets whether to consider every challenge subsequently supplied, even
+    /// after finding one which can't be bettered.
+    ///
+    /// This trades some parsing work for complete visibility: each challenge
+    /// is retained for [`PasswordClientBuilder::all_challenges`] and appears
+    /// in [`PasswordClientBuilder::rejections`] or
+    /// [`PasswordClientBuilder::build_all`] as appropriate.
+    /// [`PasswordClientBuilder::build`] still selects the preferred client.
+    /// Off by default.
+    ///
+    /// ```rust
+    /// # use http_auth::PasswordClient;
+    /// let input = r#"Digest realm="d", nonce="n", algorithm=SHA-256, Mutual"#;
+    /// let builder = PasswordClient::builder().challenges(input);
+    /// assert!(builder.all_challenges().is_empty());
+    /// let builder = PasswordClient::builder().consider_all(true).challenges(input);
+    /// let schemes: Vec<&str> = builder.all_challenges().iter().map(|c| &c.scheme[..]).collect();
+    /// assert_eq!(schemes, ["Digest", "Mutual"]);
+    /// ```
+    #[must_use]
+    pub fn consider_all(mut self, all: bool) -> Self {
+        self.consider_all = all;
+        self
+    }
+
+    /// Returns every challenge considered while
+    /// [`PasswordClientBuilder::consider_all`] was set, in the order given,
+    /// whether or not it yielded a client.
+    ///
+    /// Call this before [`PasswordClientBuilder::build`], which consumes the
+    /// builder.
+    pub fn all_challenges(&self) -> &[ChallengeOwned] {
+        &self.all_challenges
+    }
+
+    /// Skips challenges of the given scheme, compared case-insensitively, in
+    /// challenges subsequently considered.
+    ///
+    /// Unlike [`PasswordClientBuilder::prefer`], this excludes the scheme
+    /// outright, such as when a server's `Digest` support is known to be
+    /// broken. Skipped challenges are reported as rejections.
+    ///
+    /// ```rust
+    /// # use http_auth::{PasswordClient, Scheme};
+    /// let client = PasswordClient::builder()
+    ///     .ignore_scheme("digest")
+    ///     .challenges("Digest realm=\"d\", nonce=\"n\", Basic realm=\"b\"")
+    ///     .build();
+    /// # #[cfg(feature = "basic-scheme")]
+    /// assert_eq!(client.unwrap().scheme(), Scheme::Basic);
+    /// ```
+    #[must_use]
+    pub fn ignore_scheme(mut self, scheme: &str) -> Self {
+        self.ignored.push(scheme.to_owned());
+        self
+    }
+
+    /// Sets whether to accept auth-params separated by whitespace rather than
+    /// a comma, in challenges subsequently supplied via
+    /// [`PasswordClientBuilder::challenges`] or
+    /// [`PasswordClientBuilder::header_value`].
+    ///
+    /// This deviates from the RFC; see
+    /// [`ChallengeParser::allow_space_separated_params`].
+    #[must_use]
+    pub fn allow_space_separated_params(mut self, allow: bool) -> Self {
+        self.space_separated_params = allow;
+        self
+    }
+
+    /// Sets whether to reject `Digest` challenges with parameters not defined
+    /// in RFC 7616, as in [`DigestClient::try_from_strict`].
+    ///
+    /// Off by default.
     #[cfg(feature = "digest-scheme")]
-    fn complete(&self) -> bool {
-        matches!(self.0, Some(Ok(PasswordClient::Digest(_))))
+    #[cfg_attr(docsrs, doc(cfg(feature = "digest-scheme")))]
+    #[must_use]
+    pub fn strict_digest_params(mut self, strict: bool) -> Self {
+        self.strict_digest_params = strict;
+        self
+    }
+
+    /// Returns the preference for a client of the given scheme; lower is preferred.
+    fn scheme_rank(&self, scheme: &Scheme) -> u8 {
+        if self.prefer.as_ref() == Some(scheme) {
+            0
+        } else if *scheme == Scheme::Digest {
+            1
+        } else {
+            2
+        }
+    }
+
+    /// Returns the sort key for a client: first by scheme, then by `Digest`
+    /// algorithm strength. Lower is preferred.
+    fn rank(&self, client: &PasswordClient) -> (u8, u8) {
+        let scheme_rank = self.scheme_rank(&client.scheme());
+        let algorithm_rank = match client {
+            #[cfg(feature = "digest-scheme")]
+            PasswordClient::Digest(c) if c.algorithm() == digest::Algorithm::Md5 => 1,
+            _ => 0,
+        };
+        (scheme_rank, algorithm_rank)
+    }
+
+    /// Returns the lowest [`PasswordClientBuilder::rank`] of any client this
+    /// build supports.
+    fn best_possible_rank(&self) -> (u8, u8) {
+        #[allow(unused_mut)]
+        let mut best = (u8::MAX, 0);
+        #[cfg(feature = "digest-scheme")]
+        {
+            best = best.min((self.scheme_rank(&Scheme::Digest), 0));
+        }
+        #[cfg(feature = "basic-scheme")]
+        {
+            best = best.min((self.scheme_rank(&Scheme::Basic), 0));
+        }
+        best
     }
 
     /// Returns true if no more challenges need to be examined.
-    #[cfg(not(feature = "digest-scheme"))]
     fn complete(&self) -> bool {
-        matches!(self.0, Some(Ok(_)))
+        if self.consider_all {
+            return false;
+        }
+        let best = self.best_possible_rank();
+        self.clients.iter().any(|c| self.rank(c) == best)
+    }
+
+    /// Records `e` if it's the first error.
+    fn error(&mut self, e: String) {
+        if self.error.is_none() {
+            self.error = Some(e);
+        }
+    }
+
+    /// Records that the challenge with the given scheme was rejected.
+    fn reject(&mut self, scheme: &str, e: String) {
+        self.rejections.push((scheme.to_owned(), e.clone()));
+        self.error(e);
+    }
+
+    /// Returns the `(scheme, reason)` for each challenge considered so far
+    /// which didn't yield a client, in the order given.
+    ///
+    /// This includes challenges of supported schemes which failed for other
+    /// reasons, such as an unsupported `Digest` algorithm. It doesn't include
+    /// challenge lists which failed to parse.
+    ///
+    /// [`PasswordClientBuilder::build`] consumes the builder; use
+    /// [`PasswordClientBuilder::build_with_rejections`] to keep these
+    /// afterward.
+    ///
+    /// ```rust
+    /// # use http_auth::PasswordClient;
+    /// let builder = PasswordClient::builder()
+    ///     .challenges("Mutual realm=\"m\", Digest realm=\"d\", nonce=\"n\", algorithm=MD4");
+    /// let schemes: Vec<&str> = builder.rejections().iter().map(|(s, _)| &s[..]).collect();
+    /// assert_eq!(schemes, ["Mutual", "Digest"]);
+    /// builder.build().unwrap_err();
+    /// ```
+    pub fn rejections(&self) -> &[(String, String)] {
+        &self.rejections
     }
 
     /// Considers all challenges from the given `&str` challenge list.
+    ///
+    /// As with the other builder methods, the returned builder must be used;
+    /// the compiler warns if it's dropped:
+    ///
+    /// ```compile_fail
+    /// #![deny(unused_must_use)]
+    /// let builder = http_auth::PasswordClient::builder();
+    /// builder.challenges("Basic realm=\"foo\""); // does nothing
+    /// ```
+    #[must_use]
     pub fn challenges(mut self, value: &str) -> Self {
-        let mut parser = ChallengeParser::new(value);
+        let mut parser =
+            ChallengeParser::new(value).allow_space_separated_params(self.space_separated_params);
         while !self.complete() {
             match parser.next() {
                 Some(Ok(c)) => self = self.challenge(&c),
-                Some(Err(e)) if self.0.is_none() => self.0 = Some(Err(e.to_string())),
-                _ => break,
+                Some(Err(e)) => self.error(e.to_string()),
+                None => break,
             }
         }
         self
     }
 
+    /// Considers all of the given already-parsed challenges, as from
+    /// [`parse_challenges`].
+    ///
+    #[cfg_attr(
+        feature = "basic-scheme",
+        doc = r##"
+```rust
+# use http_auth::{parse_challenges, PasswordClient};
+let challenges = parse_challenges("UnsupportedSchemeA, Basic realm=\"foo\"").unwrap();
+let client = PasswordClient::builder()
+    .parsed_challenges(&challenges)
+    .build()
+    .unwrap();
+assert_eq!(client.realm(), Some("foo"));
+```
+"##
+    )]
+    #[must_use]
+    pub fn parsed_challenges<'c, 'i: 'c, I>(mut self, challenges: I) -> Self
+    where
+        I: IntoIterator<Item = &'c ChallengeRef<'i>>,
+    {
+        self.extend(challenges);
+        self
+    }
+
+    /// Installs a callback invoked once for each challenge subsequently
+    /// considered, whether or not it yields a client.
+    ///
+    /// This allows logging scheme selection with any logging system.
+    ///
+    /// ```rust
+    /// use std::sync::{Arc, Mutex};
+    /// let log = Arc::new(Mutex::new(Vec::new()));
+    /// let log2 = log.clone();
+    /// let _ = http_auth::PasswordClient::builder()
+    ///     .on_challenge(move |e| {
+    ///         log2.lock().unwrap().push(format!("{} {:?}", e.scheme, e.rejection))
+    ///     })
+    ///     .challenges("Mutual, Basic realm=\"foo\"")
+    ///     .build();
+    /// # #[cfg(feature = "basic-scheme")]
+    /// assert_eq!(
+    ///     *log.lock().unwrap(),
+    ///     ["Mutual Some(\"Unsupported scheme \\\"Mutual\\\"\")", "Basic None"],
+    /// );
+    /// ```
+    #[must_use]
+    pub fn on_challenge<F>(mut self, f: F) -> Self
+    where
+        F: FnMut(&ChallengeEvent<'_>) + Send + Sync + 'static,
+    {
+        self.on_challenge = Some(Box::new(f));
+        self
+    }
+
     /// Considers a single challenge.
+    #[must_use]
     pub fn challenge(mut self, challenge: &ChallengeRef<'_>) -> Self {
+        self.consider(challenge);
+        self
+    }
+
+    /// Implements [`PasswordClientBuilder::challenge`] by reference.
+    fn consider(&mut self, challenge: &ChallengeRef<'_>) {
         if self.complete() {
-            return self;
+            return;
+        }
+        if self.consider_all {
+            self.all_challenges.push(ChallengeOwned::from(challenge));
+        }
+        let result = self.client_for(challenge);
+        if let Some(f) = &mut self.on_challenge {
+            let param = |name: &str| {
+                challenge
+                    .params
+                    .iter()
+                    .find(|(k, _)| k.eq_ignore_ascii_case(name))
+                    .map(|(_, v)| v.as_escaped())
+            };
+            f(&ChallengeEvent {
+                scheme: challenge.scheme,
+                algorithm: param("algorithm"),
+                qop: param("qop"),
+                rejection: result.as_ref().err().map(|e| &e[..]),
+            });
+        }
+        match result {
+            Ok(c) => self.clients.push(c),
+            Err(e) => self.reject(challenge.scheme, e),
+        }
+    }
+
+    /// Returns a client for `challenge` or the reason it's unsuitable.
+    fn client_for(&self, challenge: &ChallengeRef<'_>) -> Result<PasswordClient, String> {
+        if self
+            .ignored
+            .iter()
+            .any(|s| s.eq_ignore_ascii_case(challenge.scheme))
+        {
+            return Err(format!("scheme {:?} is ignored", challenge.scheme));
         }
 
         #[cfg(feature = "digest-scheme")]
         if challenge.scheme.eq_ignore_ascii_case("Digest") {
-            match DigestClient::try_from(challenge) {
-                Ok(c) => self.0 = Some(Ok(PasswordClient::Digest(c))),
-                Err(e) if self.0.is_none() => self.0 = Some(Err(e)),
-                _ => {}
-            }
-            return self;
+            return DigestClient::from_challenge(challenge, self.strict_digest_params)
+                .map(PasswordClient::Digest);
         }
 
         #[cfg(feature = "basic-scheme")]
-        if challenge.scheme.eq_ignore_ascii_case("Basic") && !matches!(self.0, Some(Ok(_))) {
-            match BasicClient::try_from(challenge) {
-                Ok(c) => self.0 = Some(Ok(PasswordClient::Basic(c))),
-                Err(e) if self.0.is_none() => self.0 = Some(Err(e)),
-                _ => {}
-            }
-            return self;
+        if challenge.scheme.eq_ignore_ascii_case("Basic") {
+            return BasicClient::try_from(challenge).map(PasswordClient::Basic);
         }
 
-        if self.0.is_none() {
-            self.0 = Some(Err(format!("Unsupported scheme {:?}", challenge.scheme)));
+        Err(disabled_scheme_error(challenge.scheme)
+            .unwrap_or_else(|| format!("Unsupported scheme {:?}", challenge.scheme)))
+    }
+
+    /// Returns a new [`PasswordClient`] or fails.
+    ///
+    /// On failure, the error describes the first problem encountered; see
+    /// [`PasswordClientBuilder::build_with_rejections`] for every rejected
+    /// challenge.
+    #[must_use = "the builder is consumed to produce the client"]
+    #[allow(unreachable_code)] // PasswordClient is uninhabited with no schemes.
+    pub fn build(mut self) -> Result<PasswordClient, String> {
+        let best = (0..self.clients.len()).min_by_key(|&i| (self.rank(&self.clients[i]), i));
+        match best {
+            Some(i) => Ok(self.clients.swap_remove(i)),
+            None => Err(self.error.unwrap_or_else(|| "no challenges given".into())),
         }
+    }
 
-        self
+    /// Builds as in [`PasswordClientBuilder::build`], also returning the
+    /// `(scheme, reason)` for each challenge which didn't yield a client, as
+    /// in [`PasswordClientBuilder::rejections`].
+    ///
+    /// The rejections are returned whether or not the build succeeds, so a
+    /// failure can be diagnosed after the fact.
+    ///
+    /// ```rust
+    /// # use http_auth::PasswordClient;
+    /// let (client, rejections) = PasswordClient::builder()
+    ///     .challenges("Mutual realm=\"m\", Digest realm=\"d\", nonce=\"n\", algorithm=MD4")
+    ///     .build_with_rejections();
+    /// client.unwrap_err();
+    /// let schemes: Vec<&str> = rejections.iter().map(|(s, _)| &s[..]).collect();
+    /// assert_eq!(schemes, ["Mutual", "Digest"]);
+    /// ```
+    #[must_use = "the builder is consumed to produce the client"]
+    pub fn build_with_rejections(
+        mut self,
+    ) -> (Result<PasswordClient, String>, Vec<(String, String)>) {
+        let rejections = std::mem::take(&mut self.rejections);
+        (self.build(), rejections)
     }
 
-    /// Returns a new [`PasswordClient`] or fails.
-    pub fn build(self) -> Result<PasswordClient, String> {
-        self.0.unwrap_or_else(|| Err("no challenges given".into()))
+    /// Returns a [`PasswordClient`] for every supported challenge considered,
+    /// most preferred first, or fails if there are none.
+    ///
+    /// This allows falling back to another scheme if the server rejects the
+    /// first choice. Clients of equal preference are in the order their
+    /// challenges were given. Challenges after one which can't be bettered
+    /// aren't considered unless [`PasswordClientBuilder::consider_all`] is set.
+    ///
+    #[cfg_attr(
+        all(feature = "basic-scheme", feature = "digest-scheme"),
+        doc = r##"
+```rust
+# use http_auth::{PasswordClient, Scheme};
+let clients = PasswordClient::builder()
+    .challenges("Basic realm=\"b\", Digest realm=\"d\", nonce=\"n\"")
+    .build_all()
+    .unwrap();
+let schemes: Vec<Scheme> = clients.iter().map(|c| c.scheme()).collect();
+assert_eq!(schemes, [Scheme::Digest, Scheme::Basic]);
+```
+"##
+    )]
+    #[must_use = "the builder is consumed to produce the clients"]
+    pub fn build_all(mut self) -> Result<Vec<PasswordClient>, String> {
+        if self.clients.is_empty() {
+            return Err(self.error.unwrap_or_else(|| "no challenges given".into()));
+        }
+        let mut clients = std::mem::take(&mut self.clients);
+        clients.sort_by_key(|c| self.rank(c));
+        Ok(clients)
+    }
+}
+
+/// Considers each challenge, as in [`PasswordClientBuilder::parsed_challenges`].
+impl<'c, 'i: 'c> Extend<&'c ChallengeRef<'i>> for PasswordClientBuilder {
+    fn extend<I: IntoIterator<Item = &'c ChallengeRef<'i>>>(&mut self, iter: I) {
+        for c in iter {
+            self.consider(c);
+        }
+    }
+}
+
+/// Collects challenges into a builder with default settings.
+///
+/// There's no `FromIterator` impl for [`PasswordClient`] itself, as building
+/// can fail; call [`PasswordClientBuilder::build`] on the result.
+///
+/// ```rust
+/// # use http_auth::{parse_challenges, PasswordClientBuilder};
+/// let challenges = parse_challenges("UnsupportedSchemeA, Basic realm=\"foo\"").unwrap();
+/// let builder: PasswordClientBuilder = challenges.iter().collect();
+/// # #[cfg(feature = "basic-scheme")]
+/// assert_eq!(builder.build().unwrap().realm(), Some("foo"));
+/// ```
+impl<'c, 'i: 'c> std::iter::FromIterator<&'c ChallengeRef<'i>> for PasswordClientBuilder {
+    fn from_iter<I: IntoIterator<Item = &'c ChallengeRef<'i>>>(iter: I) -> Self {
+        let mut builder = PasswordClientBuilder::default();
+        builder.extend(iter);
+        builder
     }
 }
 
@@ -327,7 +1074,7 @@ impl PasswordClientBuilder {
 /// example in the [crate-level documentation](crate).
 ///
 /// For more complex scenarios, see [`PasswordClientBuilder`].
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 #[non_exhaustive]
 pub enum PasswordClient {
     #[cfg(feature = "basic-scheme")]
@@ -339,6 +1086,20 @@ pub enum PasswordClient {
     Digest(DigestClient),
 }
 
+/// Tries to create a `PasswordClient` from the single supplied owned challenge.
+impl TryFrom<&ChallengeOwned> for PasswordClient {
+    type Error = String;
+
+    fn try_from(value: &ChallengeOwned) -> Result<Self, Self::Error> {
+        let params: Vec<(&str, &str)> = value
+            .params
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect();
+        with_unescaped_params(&value.scheme, &params, |c| PasswordClient::try_from(c))
+    }
+}
+
 /// Tries to create a `PasswordClient` from the single supplied challenge.
 ///
 /// This is a convenience wrapper around [`PasswordClientBuilder`].
@@ -355,10 +1116,26 @@ impl TryFrom<&ChallengeRef<'_>> for PasswordClient {
             return Ok(PasswordClient::Digest(DigestClient::try_from(value)?));
         }
 
-        Err(format!("unsupported challenge scheme {:?}", value.scheme))
+        Err(disabled_scheme_error(value.scheme)
+            .unwrap_or_else(|| format!("unsupported challenge scheme {:?}", value.scheme)))
     }
 }
 
+/// Returns an error if `scheme` is one [`PasswordClient`] supports only with
+/// a Cargo feature which isn't enabled, so the caller knows to enable it.
+fn disabled_scheme_error(scheme: &str) -> Option<String> {
+    const DISABLED: &[(&str, &str)] = &[
+        #[cfg(not(feature = "basic-scheme"))]
+        ("Basic", "basic-scheme"),
+        #[cfg(not(feature = "digest-scheme"))]
+        ("Digest", "digest-scheme"),
+    ];
+    DISABLED
+        .iter()
+        .find(|(s, _)| s.eq_ignore_ascii_case(scheme))
+        .map(|(s, f)| format!("{:?} scheme requires the {:?} feature", s, f))
+}
+
 /// Tries to create a `PasswordClient` from the supplied `str` challenge list.
 ///
 /// This is a convenience wrapper around [`PasswordClientBuilder`].
@@ -401,7 +1178,8 @@ impl TryFrom<&http10::HeaderValue> for PasswordClient {
 
 /// Tries to create a `PasswordClient` from the supplied `http::header::GetAll` challenge lists.
 ///
-/// This is a convenience wrapper around [`PasswordClientBuilder`].
+/// This is a convenience wrapper around [`PasswordClientBuilder`]. Each value
+/// is parsed independently, so a challenge can't straddle two values.
 #[cfg(feature = "http")]
 #[cfg_attr(docsrs, doc(cfg(feature = "http")))]
 impl TryFrom<http::header::GetAll<'_, http::HeaderValue>> for PasswordClient {
@@ -418,7 +1196,8 @@ impl TryFrom<http::header::GetAll<'_, http::HeaderValue>> for PasswordClient {
 
 /// Tries to create a `PasswordClient` from the supplied `http::header::GetAll` challenge lists.
 ///
-/// This is a convenience wrapper around [`PasswordClientBuilder`].
+/// This is a convenience wrapper around [`PasswordClientBuilder`]. Each value
+/// is parsed independently, so a challenge can't straddle two values.
 #[cfg(feature = "http10")]
 #[cfg_attr(docsrs, doc(cfg(feature = "http10")))]
 impl TryFrom<http10::header::GetAll<'_, http10::HeaderValue>> for PasswordClient {
@@ -443,11 +1222,188 @@ impl PasswordClient {
         PasswordClientBuilder::default()
     }
 
+    /// Tries to create a `PasswordClient` from all challenges in the given
+    /// [`http::HeaderMap`].
+    ///
+    /// Considers `Proxy-Authenticate` headers if `proxy` is true (as in a
+    /// `407 Proxy Authentication Required` response) or `WWW-Authenticate`
+    /// headers otherwise (as in a `401 Unauthorized` response).
+    ///
+    #[cfg_attr(
+        all(feature = "http", feature = "basic-scheme"),
+        doc = r##"
+```rust
+use http::header::{HeaderMap, WWW_AUTHENTICATE};
+use http_auth::PasswordClient;
+
+let mut headers = HeaderMap::new();
+headers.append(WWW_AUTHENTICATE, "Basic realm=\"foo\"".parse().unwrap());
+let client = PasswordClient::from_header_map(&headers, false).unwrap();
+assert!(matches!(client, PasswordClient::Basic(_)));
+PasswordClient::from_header_map(&headers, true).unwrap_err();
+```
+"##
+    )]
+    #[cfg(any(feature = "http", feature = "http10"))]
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "http", feature = "http10"))))]
+    pub fn from_header_map<M: HeaderMap>(map: &M, proxy: bool) -> Result<Self, String> {
+        let mut builder = PasswordClient::builder();
+        let mut any = false;
+        for v in map.authenticate_values(proxy) {
+            builder = builder.header_value(v);
+            any = true;
+        }
+        if !any {
+            return Err(format!(
+                "no {} header",
+                if proxy {
+                    "Proxy-Authenticate"
+                } else {
+                    "WWW-Authenticate"
+                }
+            ));
+        }
+        builder.build()
+    }
+
+    /// Returns the scheme of this client.
+    pub fn scheme(&self) -> Scheme {
+        match self {
+            #[cfg(feature = "basic-scheme")]
+            Self::Basic(_) => Scheme::Basic,
+            #[cfg(feature = "digest-scheme")]
+            Self::Digest(_) => Scheme::Digest,
+
+            // See comment in `respond`.
+            #[cfg(not(any(feature = "basic-scheme", feature = "digest-scheme")))]
+            _ => unreachable!(),
+        }
+    }
+
+    /// Returns the realm, if the scheme has one.
+    ///
+    /// This is useful for looking up credentials, and is currently always
+    /// `Some` since both `Basic` and `Digest` challenges require a realm.
+    ///
+    #[cfg_attr(
+        feature = "basic-scheme",
+        doc = r##"
+```rust
+# use std::convert::TryFrom as _;
+let client = http_auth::PasswordClient::try_from("Basic realm=\"foo\"").unwrap();
+assert_eq!(client.realm(), Some("foo"));
+```
+"##
+    )]
+    pub fn realm(&self) -> Option<&str> {
+        match self {
+            #[cfg(feature = "basic-scheme")]
+            Self::Basic(c) => Some(c.realm()),
+            #[cfg(feature = "digest-scheme")]
+            Self::Digest(c) => Some(c.realm()),
+
+            // See comment in `respond`.
+            #[cfg(not(any(feature = "basic-scheme", feature = "digest-scheme")))]
+            _ => unreachable!(),
+        }
+    }
+
+    /// Returns a copy of this client with per-connection state reset, as for
+    /// use on a new connection.
+    ///
+    /// Unlike `clone`, which preserves all state, this resets the `Digest`
+    /// nonce count, so the next response sends `nc=00000001` with a fresh
+    /// client nonce. `Basic` has no such state, so this is equivalent to
+    /// `clone` for it.
+    pub fn fresh(&self) -> PasswordClient {
+        match self {
+            #[cfg(feature = "basic-scheme")]
+            Self::Basic(c) => Self::Basic(c.clone()),
+            #[cfg(feature = "digest-scheme")]
+            Self::Digest(c) => {
+                let mut c = c.clone();
+                c.reset_nonce_count();
+                Self::Digest(c)
+            }
+
+            // See comment in `respond`.
+            #[cfg(not(any(feature = "basic-scheme", feature = "digest-scheme")))]
+            _ => unreachable!(),
+        }
+    }
+
+    /// Returns true if responding uses the request's `uri`, `method`, and
+    /// possibly `body` from [`PasswordParams`].
+    ///
+    /// This is false for `Basic`, which only uses the credentials, so callers
+    /// can skip gathering the request context.
+    ///
+    #[cfg_attr(
+        feature = "basic-scheme",
+        doc = r##"
+```rust
+# use std::convert::TryFrom as _;
+let client = http_auth::PasswordClient::try_from("Basic realm=\"foo\"").unwrap();
+assert!(!client.needs_request_context());
+```
+"##
+    )]
+    pub fn needs_request_context(&self) -> bool {
+        match self {
+            #[cfg(feature = "basic-scheme")]
+            Self::Basic(_) => false,
+            #[cfg(feature = "digest-scheme")]
+            Self::Digest(_) => true,
+
+            // See comment in `respond`.
+            #[cfg(not(any(feature = "basic-scheme", feature = "digest-scheme")))]
+            _ => unreachable!(),
+        }
+    }
+
+    /// Responds with the given credentials if this is a `Basic` client, or
+    /// returns `None` otherwise.
+    ///
+    /// This is a shortcut for callers which only use `Basic`, so they needn't
+    /// construct [`PasswordParams`] with a request context `Basic` ignores.
+    ///
+    #[cfg_attr(
+        all(feature = "basic-scheme", feature = "digest-scheme"),
+        doc = r##"
+```rust
+# use std::convert::TryFrom as _;
+use http_auth::PasswordClient;
+let client = PasswordClient::try_from("Basic realm=\"foo\"").unwrap();
+assert_eq!(
+    client.respond_basic("Aladdin", "open sesame").as_deref(),
+    Some("Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ=="),
+);
+let client = PasswordClient::try_from("Digest realm=\"foo\", nonce=\"n\"").unwrap();
+assert_eq!(client.respond_basic("Aladdin", "open sesame"), None);
+```
+"##
+    )]
+    #[allow(unused_variables)] // credentials are unused with no basic-scheme.
+    #[must_use]
+    pub fn respond_basic(&self, username: &str, password: &str) -> Option<String> {
+        match self {
+            #[cfg(feature = "basic-scheme")]
+            Self::Basic(c) => Some(c.respond(username, password)),
+            #[cfg(feature = "digest-scheme")]
+            Self::Digest(_) => None,
+
+            // See comment in `respond`.
+            #[cfg(not(any(feature = "basic-scheme", feature = "digest-scheme")))]
+            _ => unreachable!(),
+        }
+    }
+
     /// Responds to the challenge with the supplied parameters.
     ///
     /// The caller should use the returned string as an `Authorization` or
     /// `Proxy-Authorization` header value.
     #[allow(unused_variables)] // p is unused with no features.
+    #[must_use = "the response must be sent as a header value"]
     pub fn respond(&mut self, p: &PasswordParams) -> Result<String, String> {
         match self {
             #[cfg(feature = "basic-scheme")]
@@ -462,6 +1418,67 @@ impl PasswordClient {
             _ => unreachable!(),
         }
     }
+
+    /// Responds to the challenge, returning the header name and value to set.
+    ///
+    /// The name is `Proxy-Authorization` for [`AuthTarget::Proxy`] (when
+    /// responding to a `Proxy-Authenticate` challenge) or `Authorization` for
+    /// [`AuthTarget::Origin`]. The return type selects the `http` crate
+    /// version; see [`AuthorizationHeader`].
+    #[cfg_attr(
+        all(feature = "http", feature = "basic-scheme"),
+        doc = r##"
+```rust
+# use std::convert::TryFrom as _;
+use http_auth::{AuthTarget, PasswordClient, PasswordParams};
+let mut client = PasswordClient::try_from("Basic realm=\"foo\"").unwrap();
+let (name, value): (_, http::HeaderValue) = client
+    .respond_header(
+        &PasswordParams {
+            username: "Aladdin",
+            password: "open sesame",
+            uri: "/",
+            method: "GET",
+            body: Some(&[]),
+        },
+        AuthTarget::Proxy,
+    )
+    .unwrap();
+assert_eq!(name, http::header::PROXY_AUTHORIZATION);
+assert_eq!(value, "Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ==");
+```
+"##
+    )]
+    #[cfg(any(feature = "http", feature = "http10"))]
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "http", feature = "http10"))))]
+    pub fn respond_header<V: AuthorizationHeader>(
+        &mut self,
+        p: &PasswordParams,
+        target: AuthTarget,
+    ) -> Result<(V::Name, V), String> {
+        let value = V::from_response(self.respond(p)?)?;
+        Ok((V::name(target), value))
+    }
+
+    /// Processes the previous response's `Authentication-Info` or
+    /// `Proxy-Authentication-Info` header value, if any, then responds as in
+    /// [`PasswordClient::respond`].
+    ///
+    /// With `auth_info` of `None`, this is equivalent to `respond`. The
+    /// `Basic` scheme ignores `auth_info`. See
+    /// [`DigestClient::process_auth_info`] for the `Digest` scheme.
+    #[allow(unused_variables)] // auth_info is unused with no digest-scheme.
+    pub fn respond_with_auth_info(
+        &mut self,
+        auth_info: Option<&str>,
+        p: &PasswordParams,
+    ) -> Result<String, String> {
+        #[cfg(feature = "digest-scheme")]
+        if let (Self::Digest(c), Some(auth_info)) = (&mut *self, auth_info) {
+            c.process_auth_info(auth_info)?;
+        }
+        self.respond(p)
+    }
 }
 
 /// Parameters for responding to a password challenge.
@@ -480,7 +1497,28 @@ impl PasswordClient {
 /// Note that most of these fields are only needed for [`DigestClient`]. Callers
 /// that only care about the `Basic` challenge scheme can use
 /// [`BasicClient::respond`] directly with only username and password.
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+///
+/// The `Debug` output redacts the password.
+///
+/// ## Construction
+///
+/// The fields are public, so this can be constructed with struct literal
+/// syntax. However, such code will break if a field is added. To avoid that,
+/// use [`PasswordParams::new`] and the `with_*` methods, which future
+/// additions will leave alone by giving new fields a default:
+///
+/// ```rust
+/// let body = b"";
+/// let p = http_auth::PasswordParams::new("Aladdin", "open sesame")
+///     .with_method("GET")
+///     .with_uri("/")
+///     .with_body(Some(&body[..]));
+/// assert_eq!(p.uri, "/");
+/// ```
+///
+/// A future major version may mark this struct `#[non_exhaustive]`, which
+/// would disallow the struct literal syntax.
+#[derive(Copy, Clone, Eq, PartialEq)]
 pub struct PasswordParams<'a> {
     pub username: &'a str,
     pub password: &'a str,
@@ -503,10 +1541,17 @@ pub struct PasswordParams<'a> {
     /// matches RFC 2617 section 3.2.2.5, and [Appendix
     /// A](https://datatracker.ietf.org/doc/html/rfc7616#appendix-A) doesn't
     /// mention a change from RFC 2617.
+    ///
+    /// For an asterisk-form request such as `OPTIONS *` (also used by RTSP),
+    /// this should be `*`; it's used verbatim.
     pub uri: &'a str,
 
     /// The HTTP method, such as `GET`.
     ///
+    /// This is used verbatim, without case normalization, so any method token
+    /// works, including extension methods and RTSP methods such as
+    /// `DESCRIBE`.
+    ///
     /// When using the `http` crate, use the return value of
     /// [`http::Method::as_str`].
     pub method: &'a str,
@@ -520,13 +1565,63 @@ pub struct PasswordParams<'a> {
     pub body: Option<&'a [u8]>,
 }
 
+impl<'a> PasswordParams<'a> {
+    /// Creates parameters with the given credentials.
+    ///
+    /// The `Digest`-only fields default to empty: `uri` and `method` are `""`
+    /// and `body` is `None`. Callers which may respond to `Digest` challenges
+    /// must set `uri` and `method` to match the request.
+    pub fn new(username: &'a str, password: &'a str) -> Self {
+        PasswordParams {
+            username,
+            password,
+            uri: "",
+            method: "",
+            body: None,
+        }
+    }
+
+    /// Sets [`PasswordParams::uri`].
+    #[inline]
+    pub fn with_uri(mut self, uri: &'a str) -> Self {
+        self.uri = uri;
+        self
+    }
+
+    /// Sets [`PasswordParams::method`].
+    #[inline]
+    pub fn with_method(mut self, method: &'a str) -> Self {
+        self.method = method;
+        self
+    }
+
+    /// Sets [`PasswordParams::body`].
+    #[inline]
+    pub fn with_body(mut self, body: Option<&'a [u8]>) -> Self {
+        self.body = body;
+        self
+    }
+}
+
+impl std::fmt::Debug for PasswordParams<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PasswordParams")
+            .field("username", &self.username)
+            .field("password", &"<redacted>")
+            .field("uri", &self.uri)
+            .field("method", &self.method)
+            .field("body", &self.body)
+            .finish()
+    }
+}
+
 /// Parses a list of challenges into a `Vec`.
 ///
 /// Most callers don't need to directly parse; see [`PasswordClient`] instead.
 ///
 /// This is a shorthand for `parser::ChallengeParser::new(input).collect()`. Use
-/// [`crate::parser::ChallengeParser`] directly when you want to parse lazily,
-/// avoid allocation, and/or see any well-formed challenges before an error.
+/// [`iter_challenges`] when you want to parse lazily, avoid allocation, and/or
+/// see any well-formed challenges before an error.
 ///
 /// ## Example
 ///
@@ -552,18 +1647,65 @@ pub struct PasswordParams<'a> {
 /// parse_challenges("UnsupportedSchemeA, Basic realm=\"foo\", error error").unwrap_err();
 /// ```
 #[inline]
-pub fn parse_challenges(input: &str) -> Result<Vec<ChallengeRef>, parser::Error> {
+pub fn parse_challenges(input: &str) -> Result<Vec<ChallengeRef<'_>>, parser::Error<'_>> {
     parser::ChallengeParser::new(input).collect()
 }
 
+/// Parses a list of challenges lazily.
+///
+/// This is a shorthand for [`ChallengeParser::new`]. Each item is a
+/// `Result`: well-formed challenges are yielded until the first syntax error,
+/// which is yielded as `Err`, and then iteration ends.
+///
+/// ```rust
+/// use http_auth::iter_challenges;
+/// for c in iter_challenges("Negotiate, Basic realm=\"foo\", error error") {
+///     match c {
+///         Ok(c) if c.scheme == "Basic" => break, // never sees the error.
+///         Ok(_) => {}
+///         Err(e) => panic!("{}", e),
+///     }
+/// }
+/// ```
+#[inline]
+pub fn iter_challenges(input: &str) -> ChallengeParser<'_> {
+    ChallengeParser::new(input)
+}
+
+/// Returns the scheme names of all challenges in `input`, in order.
+///
+/// This is useful for capability checks or metrics, when the parameters
+/// aren't needed. Like [`parse_challenges`], returns `Err` if there is a
+/// syntax error anywhere in the input.
+///
+/// ```rust
+/// assert_eq!(
+///     http_auth::challenge_schemes(r#"Negotiate, Basic realm="foo", Digest realm="bar", nonce="n""#)
+///         .unwrap(),
+///     ["Negotiate", "Basic", "Digest"],
+/// );
+/// ```
+pub fn challenge_schemes(input: &str) -> Result<Vec<String>, parser::Error<'_>> {
+    ChallengeParser::new(input)
+        .map(|c| c.map(|c| c.scheme.to_owned()))
+        .collect()
+}
+
 /// Parsed challenge parameter value used within [`ChallengeRef`].
-#[derive(Copy, Clone, Eq, PartialEq)]
+///
+/// Equality and hashing operate on the escaped form, so `"a\\b"` and `"ab"`
+/// are distinct values even though both unescape to `ab`. Compare
+/// [`ParamValue::to_unescaped`] results when that distinction doesn't matter.
+#[derive(Copy, Clone, Eq, Hash, PartialEq)]
 pub struct ParamValue<'i> {
     /// The number of backslash escapes in a quoted-text parameter; 0 for a plain token.
     escapes: usize,
 
     /// The escaped string, which must be pure ASCII (no bytes >= 128) and be
-    /// consistent with `escapes`.
+    /// consistent with `escapes`: it contains exactly `escapes` backslashes
+    /// which each begin a two-byte escape sequence, so `escapes <=
+    /// escaped.len() / 2`. The parser, [`ParamValue::try_from_escaped`], and
+    /// [`ParamValue::new`] all check this.
     escaped: &'i str,
 }
 
@@ -575,7 +1717,7 @@ impl<'i> ParamValue<'i> {
         let mut escapes = 0;
         let mut pos = 0;
         while pos < escaped.len() {
-            let slash = memchr::memchr(b'\\', &escaped.as_bytes()[pos..]).map(|off| pos + off);
+            let slash = find_backslash(&escaped.as_bytes()[pos..]).map(|off| pos + off);
             for i in pos..slash.unwrap_or(escaped.len()) {
                 if (char_classes(escaped.as_bytes()[i]) & C_QDTEXT) == 0 {
                     return Err(format!("{:?} has non-qdtext at byte {}", escaped, i));
@@ -601,21 +1743,67 @@ impl<'i> ParamValue<'i> {
         Ok(Self { escaped, escapes })
     }
 
+    /// Creates a new `ParamValue` for the unquoted `token` form, validating
+    /// that `token` is non-empty and consists only of `tchar`s.
+    ///
+    /// ```rust
+    /// use http_auth::ParamValue;
+    /// assert_eq!(ParamValue::plain("SHA-256").unwrap().as_escaped(), "SHA-256");
+    /// ParamValue::plain("two words").unwrap_err();
+    /// ```
+    pub fn plain(token: &'i str) -> Result<Self, String> {
+        if token.is_empty() {
+            return Err("empty token".into());
+        }
+        if let Some(i) = token.bytes().position(|b| (char_classes(b) & C_TCHAR) == 0) {
+            return Err(format!("{:?} has non-tchar at byte {}", token, i));
+        }
+        Ok(Self {
+            escapes: 0,
+            escaped: token,
+        })
+    }
+
+    /// Escapes an arbitrary value for the `quoted-string` form.
+    ///
+    /// `ParamValue` borrows its escaped form, so this returns it as a `String`
+    /// for the caller to keep, then pass to [`ParamValue::try_from_escaped`].
+    /// Fails if `unescaped` contains a byte which can't be escaped, such as a
+    /// control character or non-ASCII character.
+    ///
+    /// ```rust
+    /// use http_auth::ParamValue;
+    /// let escaped = ParamValue::escape("Login to \"apps\"").unwrap();
+    /// let v = ParamValue::try_from_escaped(&escaped).unwrap();
+    /// assert_eq!(v.as_escaped(), r#"Login to \"apps\""#);
+    /// assert_eq!(v.to_unescaped(), "Login to \"apps\"");
+    /// ```
+    pub fn escape(unescaped: &str) -> Result<String, String> {
+        let mut out = String::with_capacity(unescaped.len());
+        append_escaped(unescaped, &mut out)
+            .ok_or_else(|| format!("{:?} can't be escaped", unescaped))?;
+        Ok(out)
+    }
+
     /// Creates a new param, panicking if invariants are not satisfied.
     /// This is not part of the stable API; it's just for the fuzz tester to use.
     #[doc(hidden)]
     pub fn new(escapes: usize, escaped: &'i str) -> Self {
         let mut pos = 0;
         for escape in 0..escapes {
-            match memchr::memchr(b'\\', &escaped.as_bytes()[pos..]) {
+            match find_backslash(&escaped.as_bytes()[pos..]) {
                 Some(rel_pos) => pos += rel_pos + 2,
                 None => panic!(
                     "expected {} backslashes in {:?}, ran out after {}",
                     escapes, escaped, escape
                 ),
             };
+            match escaped.as_bytes().get(pos - 1) {
+                Some(&b) if (char_classes(b) & C_ESCAPABLE) != 0 => {}
+                _ => panic!("invalid quoted-pair escape in {:?}", escaped),
+            }
         }
-        if memchr::memchr(b'\\', &escaped.as_bytes()[pos..]).is_some() {
+        if find_backslash(&escaped.as_bytes()[pos..]).is_some() {
             panic!(
                 "expected {} backslashes in {:?}, are more",
                 escapes, escaped
@@ -626,10 +1814,15 @@ impl<'i> ParamValue<'i> {
 
     /// Appends the unescaped form of this parameter to the supplied string.
     pub fn append_unescaped(&self, to: &mut String) {
-        to.reserve(self.escaped.len() - self.escapes);
+        if self.escapes == 0 {
+            // Fast path for the common case.
+            to.push_str(self.escaped);
+            return;
+        }
+        to.reserve(self.unescaped_len());
         let mut first_unwritten = 0;
         for _ in 0..self.escapes {
-            let i = match memchr::memchr(b'\\', &self.escaped.as_bytes()[first_unwritten..]) {
+            let i = match find_backslash(&self.escaped.as_bytes()[first_unwritten..]) {
                 Some(rel_i) => first_unwritten + rel_i,
                 None => panic!("bad ParamValues; not as many backslash escapes as promised"),
             };
@@ -640,14 +1833,87 @@ impl<'i> ParamValue<'i> {
         to.push_str(&self.escaped[first_unwritten..]);
     }
 
+    /// Returns true if the unescaped form of this parameter starts with `prefix`.
+    ///
+    /// This is equivalent to `self.to_unescaped().starts_with(prefix)` but
+    /// doesn't allocate and stops at the first mismatch.
+    ///
+    /// ```rust
+    /// use http_auth::ParamValue;
+    /// let v = ParamValue::try_from_escaped(r#"/a\"b /c"#).unwrap();
+    /// assert!(v.unescaped_starts_with("/a\"b"));
+    /// assert!(!v.unescaped_starts_with("/a\\"));
+    /// ```
+    pub fn unescaped_starts_with(&self, prefix: &str) -> bool {
+        if prefix.len() > self.unescaped_len() {
+            return false;
+        }
+        let escaped = self.escaped.as_bytes();
+        let mut prefix = prefix.as_bytes();
+        let mut first_uncompared = 0;
+        for _ in 0..self.escapes {
+            let i = match find_backslash(&escaped[first_uncompared..]) {
+                Some(rel_i) => first_uncompared + rel_i,
+                None => panic!("bad ParamValues; not as many backslash escapes as promised"),
+            };
+            let chunk = &escaped[first_uncompared..i];
+            if prefix.len() <= chunk.len() {
+                return chunk.starts_with(prefix);
+            }
+            if !prefix.starts_with(chunk) || prefix[chunk.len()] != escaped[i + 1] {
+                return false;
+            }
+            prefix = &prefix[chunk.len() + 1..];
+            first_uncompared = i + 2;
+        }
+        escaped[first_uncompared..].starts_with(prefix)
+    }
+
     /// Returns the unescaped length of this parameter; cheap.
     #[inline]
     pub fn unescaped_len(&self) -> usize {
-        self.escaped.len() - self.escapes
+        // Each escape removes one byte. `escapes` can't exceed half the length
+        // per the invariant above; saturate anyway rather than wrap, so a bug
+        // can't cause a huge allocation in `append_unescaped`.
+        debug_assert!(self.escapes <= self.escaped.len() / 2);
+        self.escaped.len().saturating_sub(self.escapes)
+    }
+
+    /// Returns the unescaped length of this parameter; an alias for
+    /// [`ParamValue::unescaped_len`].
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.unescaped_len()
+    }
+
+    /// Returns true if the unescaped value is empty, as in `realm=""`.
+    ///
+    /// ```rust
+    /// use http_auth::ParamValue;
+    /// assert!(ParamValue::try_from_escaped("").unwrap().is_empty());
+    /// assert!(!ParamValue::try_from_escaped(r"\\").unwrap().is_empty());
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.escaped.is_empty()
+    }
+
+    /// Returns the unescaped form of this parameter, borrowing when it has no
+    /// escapes.
+    #[cfg(feature = "digest-scheme")]
+    pub(crate) fn unescaped_cow(&self) -> std::borrow::Cow<'i, str> {
+        if self.escapes == 0 {
+            std::borrow::Cow::Borrowed(self.escaped)
+        } else {
+            std::borrow::Cow::Owned(self.to_unescaped())
+        }
     }
 
     /// Returns the unescaped form of this parameter as a fresh `String`.
     pub fn to_unescaped(&self) -> String {
+        if self.escapes == 0 {
+            return self.escaped.to_owned();
+        }
         let mut to = String::new();
         self.append_unescaped(&mut to);
         to
@@ -673,6 +1939,21 @@ impl<'i> ParamValue<'i> {
     pub fn as_escaped(&self) -> &'i str {
         self.escaped
     }
+
+    /// Returns the escaped string, unquoted, as bytes.
+    ///
+    /// This is the same as `as_escaped().as_bytes()` today, but would remain
+    /// available if values could someday hold non-UTF-8 `obs-text`.
+    ///
+    /// ```rust
+    /// use http_auth::ParamValue;
+    /// let v = ParamValue::try_from_escaped(r#"a\"b"#).unwrap();
+    /// assert_eq!(v.as_escaped_bytes(), br#"a\"b"#);
+    /// ```
+    #[inline]
+    pub fn as_escaped_bytes(&self) -> &'i [u8] {
+        self.escaped.as_bytes()
+    }
 }
 
 impl<'i> std::fmt::Debug for ParamValue<'i> {
@@ -681,10 +1962,88 @@ impl<'i> std::fmt::Debug for ParamValue<'i> {
     }
 }
 
+/// Appends `value` to `out` in escaped form, suitable for the inside of a
+/// `quoted-string`, returning the number of escapes.
+///
+/// Returns `None` if `value` has a byte which can't be represented even with a
+/// `quoted-pair` escape. `out` may have been partially written in this case.
+fn append_escaped(value: &str, out: &mut String) -> Option<usize> {
+    let mut escapes = 0;
+    let mut first_unwritten = 0;
+    for (i, &b) in value.as_bytes().iter().enumerate() {
+        // Note that bytes >= 128 are in neither C_QDTEXT nor C_ESCAPABLE, so every allowed byte
+        // is a full UTF-8 code point.
+        let class = char_classes(b);
+        if (class & C_QDTEXT) != 0 {
+            // Just advance.
+        } else if (class & C_ESCAPABLE) != 0 {
+            out.push_str(&value[first_unwritten..i]);
+            out.push('\\');
+            out.push(char::from(b));
+            first_unwritten = i + 1;
+            escapes += 1;
+        } else {
+            return None;
+        }
+    }
+    out.push_str(&value[first_unwritten..]);
+    Some(escapes)
+}
+
+/// Returns the position of the first backslash in `haystack`, if any.
+#[cfg(feature = "memchr")]
+#[inline]
+fn find_backslash(haystack: &[u8]) -> Option<usize> {
+    memchr::memchr(b'\\', haystack)
+}
+
+/// Returns the position of the first backslash in `haystack`, if any.
+#[cfg(not(feature = "memchr"))]
+#[inline]
+fn find_backslash(haystack: &[u8]) -> Option<usize> {
+    haystack.iter().position(|&b| b == b'\\')
+}
+
+/// Calls `f` with a [`ChallengeRef`] of the given scheme and already-unescaped
+/// parameters.
+///
+/// This backs the `from_params` constructors and [`ChallengeOwned`]
+/// conversion, which skip the round trip through header syntax.
+fn with_unescaped_params<T>(
+    scheme: &str,
+    params: &[(&str, &str)],
+    f: impl FnOnce(&ChallengeRef<'_>) -> Result<T, String>,
+) -> Result<T, String> {
+    let mut buf = String::new();
+    let mut ends = Vec::with_capacity(params.len());
+    for &(k, v) in params {
+        let escapes =
+            append_escaped(v, &mut buf).ok_or_else(|| format!("invalid {} value {:?}", k, v))?;
+        ends.push((buf.len(), escapes));
+    }
+    let mut challenge = ChallengeRef::new(scheme);
+    challenge.params.reserve(params.len());
+    let mut start = 0;
+    for (&(k, _), &(end, escapes)) in params.iter().zip(&ends) {
+        challenge.params.push((
+            k,
+            ParamValue {
+                escapes,
+                escaped: &buf[start..end],
+            },
+        ));
+        start = end;
+    }
+    f(&challenge)
+}
+
 #[cfg(test)]
 mod tests {
+    use std::convert::TryFrom as _;
+
+    use crate::table::C_ATTR;
     use crate::ParamValue;
-    use crate::{C_ATTR, C_ESCAPABLE, C_OWS, C_QDTEXT, C_TCHAR};
+    use crate::{C_ESCAPABLE, C_OWS, C_QDTEXT, C_TCHAR};
 
     /// Prints the character classes of all ASCII bytes from the table.
     ///
@@ -739,6 +2098,9 @@ mod tests {
         ParamValue::try_from_escaped("\"").unwrap_err(); // not valid qdtext
         ParamValue::try_from_escaped("\n").unwrap_err(); // not valid qdtext
         ParamValue::try_from_escaped("\\\n").unwrap_err(); // not valid escape
+        ParamValue::try_from_escaped("\\\r").unwrap_err(); // not valid escape
+        ParamValue::try_from_escaped("\\\r\\\n").unwrap_err(); // not valid escape
+        assert!(std::panic::catch_unwind(|| ParamValue::new(1, "\\\r")).is_err());
     }
 
     #[test]
@@ -792,4 +2154,532 @@ mod tests {
             "foobar"
         );
     }
+
+    #[test]
+    fn hash_dedup() {
+        let mut set = std::collections::HashSet::new();
+        for c in crate::parse_challenges(
+            r#"Basic realm="foo", Basic realm=foo, Basic realm="f\oo", Basic realm="bar""#,
+        )
+        .unwrap()
+        {
+            set.insert(c);
+        }
+
+        // Unquoted and quoted forms without escapes are equal; escaped forms are distinct.
+        assert_eq!(set.len(), 3);
+    }
+
+    #[cfg(all(feature = "basic-scheme", feature = "digest-scheme"))]
+    #[test]
+    fn prefer() {
+        use crate::{PasswordClient, Scheme};
+        let challenges =
+            "Basic realm=\"foo\", Digest realm=\"foo\", nonce=\"bar\", Basic realm=\"baz\"";
+        let c = PasswordClient::builder()
+            .challenges(challenges)
+            .build()
+            .unwrap();
+        assert_eq!(c.scheme(), Scheme::Digest);
+        let c = PasswordClient::builder()
+            .prefer(Scheme::Basic)
+            .challenges(challenges)
+            .build()
+            .unwrap();
+        assert_eq!(c.scheme(), Scheme::Basic);
+        assert_eq!(c.realm(), Some("foo"));
+
+        // Falls back to a non-preferred scheme if necessary.
+        let c = PasswordClient::builder()
+            .prefer(Scheme::Basic)
+            .challenges("Digest realm=\"foo\", nonce=\"bar\"")
+            .build()
+            .unwrap();
+        assert_eq!(c.scheme(), Scheme::Digest);
+    }
+
+    #[cfg(feature = "digest-scheme")]
+    #[test]
+    fn respond_with_auth_info() {
+        use std::convert::TryFrom as _;
+        let mut client =
+            crate::PasswordClient::try_from(r#"Digest realm="r", nonce="a", qop="auth""#).unwrap();
+        let p = crate::PasswordParams {
+            username: "u",
+            password: "p",
+            uri: "/",
+            method: "GET",
+            body: None,
+        };
+        let first = client.respond_with_auth_info(None, &p).unwrap();
+        assert!(first.contains(r#"nonce="a""#), "{}", first);
+        let second = client
+            .respond_with_auth_info(Some(r#"nextnonce="b""#), &p)
+            .unwrap();
+        assert!(second.contains(r#"nonce="b""#), "{}", second);
+        assert!(second.contains("nc=00000001"), "{}", second);
+    }
+
+    #[test]
+    fn unescaped_starts_with() {
+        let v = ParamValue::new(2, r#"a\"bc\\d"#);
+        for prefix in ["", "a", "a\"", "a\"b", "a\"bc\\", "a\"bc\\d"] {
+            assert!(v.unescaped_starts_with(prefix), "{:?}", prefix);
+        }
+        for prefix in ["b", "a\\", "a\"c", "a\"bc\"", "a\"bc\\d ", "a\"bc\\\\d"] {
+            assert!(!v.unescaped_starts_with(prefix), "{:?}", prefix);
+        }
+    }
+
+    #[test]
+    fn scheme_round_trip() {
+        use crate::Scheme;
+        for name in ["Basic", "Digest", "Bearer", "Negotiate", "Mutual"] {
+            let scheme: Scheme = name.parse().unwrap();
+            assert_eq!(scheme.to_string(), name);
+            assert_eq!(
+                name.to_ascii_uppercase().parse::<Scheme>().unwrap() == scheme,
+                name != "Mutual"
+            );
+        }
+        "".parse::<Scheme>().unwrap_err();
+        "a=b".parse::<Scheme>().unwrap_err();
+    }
+
+    #[test]
+    fn bare_negotiate() {
+        use std::convert::TryFrom as _;
+        assert_eq!(
+            crate::parse_challenges("Negotiate").unwrap(),
+            vec![crate::ChallengeRef::new("Negotiate")]
+        );
+        let e = crate::PasswordClient::try_from("Negotiate").unwrap_err();
+        assert!(e.contains("\"Negotiate\""), "{}", e);
+    }
+
+    #[cfg(all(feature = "http10", feature = "basic-scheme"))]
+    #[test]
+    fn respond_header_http10() {
+        use std::convert::TryFrom as _;
+        let mut client = crate::PasswordClient::try_from("Basic realm=\"foo\"").unwrap();
+        let p = crate::PasswordParams {
+            username: "Aladdin",
+            password: "open sesame",
+            uri: "/",
+            method: "GET",
+            body: None,
+        };
+        let (name, value): (_, http10::HeaderValue) = client
+            .respond_header(&p, crate::AuthTarget::Origin)
+            .unwrap();
+        assert_eq!(name, http10::header::AUTHORIZATION);
+        assert_eq!(value, "Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ==");
+        let (name, _): (_, http10::HeaderValue) =
+            client.respond_header(&p, crate::AuthTarget::Proxy).unwrap();
+        assert_eq!(name, http10::header::PROXY_AUTHORIZATION);
+    }
+
+    /// Tests that each header value is parsed independently, so a challenge
+    /// can't straddle two values.
+    #[cfg(all(feature = "http", feature = "basic-scheme", feature = "digest-scheme"))]
+    #[test]
+    fn split_across_values() {
+        use http::header::{HeaderMap, HeaderValue, WWW_AUTHENTICATE};
+        use std::convert::TryFrom as _;
+        let client = |values: &[&'static str]| {
+            let mut headers = HeaderMap::new();
+            for &v in values {
+                headers.append(WWW_AUTHENTICATE, HeaderValue::from_static(v));
+            }
+            crate::PasswordClient::try_from(headers.get_all(WWW_AUTHENTICATE))
+        };
+
+        // Split mid-param.
+        client(&["Basic realm=\"fo", "o\""]).unwrap_err();
+
+        // Split between params: neither value is a complete Digest challenge.
+        client(&[r#"Digest realm="r","#, r#"nonce="n", qop="auth""#]).unwrap_err();
+
+        // The second value's params don't attach to the first value's Basic.
+        let c = client(&["Basic", r#"realm="r""#]);
+        assert!(c.is_err(), "{:?}", c);
+
+        // Complete challenges in separate values are fine.
+        let c = client(&[
+            r#"Basic realm="r""#,
+            r#"Digest realm="r", nonce="n", qop="auth""#,
+        ])
+        .unwrap();
+        assert_eq!(c.scheme(), crate::Scheme::Digest);
+    }
+
+    #[cfg(all(feature = "basic-scheme", feature = "digest-scheme"))]
+    #[test]
+    fn challenge_owned() {
+        use crate::{ChallengeOwned, PasswordClient, Scheme};
+        use std::convert::TryFrom as _;
+        let header = r#"Basic realm="a \"b\"", Digest realm="r", nonce="n", qop="auth""#;
+        let owned: Vec<ChallengeOwned> = crate::parse_challenges(header)
+            .unwrap()
+            .iter()
+            .map(ChallengeOwned::from)
+            .collect();
+        assert_eq!(owned[0].params[0].1, "a \"b\"");
+
+        let basic = PasswordClient::try_from(&owned[0]).unwrap();
+        assert_eq!(basic.scheme(), Scheme::Basic);
+        assert_eq!(basic.realm(), Some("a \"b\""));
+
+        let digest = PasswordClient::try_from(&owned[1]).unwrap();
+        let from_ref =
+            PasswordClient::try_from(r#"Digest realm="r", nonce="n", qop="auth""#).unwrap();
+        assert_eq!(digest, from_ref);
+    }
+
+    #[test]
+    fn display_order() {
+        let header = r#"Newauth z=1, a="", m="x y", b="\\", z="2""#;
+        let c = &crate::parse_challenges(header).unwrap()[0];
+        let displayed = c.to_string();
+        assert_eq!(displayed, r#"Newauth z=1, a="", m="x y", b="\\", z=2"#);
+        assert_eq!(&crate::parse_challenges(&displayed).unwrap()[0], c);
+        assert_eq!(
+            crate::ChallengeRef::new("Negotiate").to_string(),
+            "Negotiate"
+        );
+    }
+
+    #[cfg(feature = "digest-scheme")]
+    #[test]
+    fn fresh() {
+        use std::convert::TryFrom as _;
+        let mut client =
+            crate::PasswordClient::try_from(r#"Digest realm="r", nonce="n", qop="auth""#).unwrap();
+        let p = crate::PasswordParams::new("u", "p")
+            .with_uri("/")
+            .with_method("GET");
+        client.respond(&p).unwrap();
+        let mut cloned = client.clone();
+        let mut fresh = client.fresh();
+        let r = cloned.respond(&p).unwrap();
+        assert!(r.contains("nc=00000002"), "{}", r);
+        let r = fresh.respond(&p).unwrap();
+        assert!(r.contains("nc=00000001"), "{}", r);
+    }
+
+    #[cfg(all(feature = "basic-scheme", feature = "digest-scheme"))]
+    #[test]
+    fn build_all() {
+        use crate::{PasswordClient, Scheme};
+        let challenges =
+            r#"Digest realm="d1", nonce="n", Basic realm="b", Digest realm="d2", nonce="n""#;
+        let clients = PasswordClient::builder()
+            .challenges(challenges)
+            .build_all()
+            .unwrap();
+        let realms: Vec<_> = clients.iter().map(|c| c.realm().unwrap()).collect();
+        assert_eq!(realms, ["d1", "d2", "b"]);
+        let clients = PasswordClient::builder()
+            .prefer(Scheme::Basic)
+            .challenges(challenges)
+            .build_all()
+            .unwrap();
+        let realms: Vec<_> = clients.iter().map(|c| c.realm().unwrap()).collect();
+        assert_eq!(realms, ["b", "d1"]); // stops at the preferred Basic challenge.
+        PasswordClient::builder()
+            .challenges("Unknown")
+            .build_all()
+            .unwrap_err();
+    }
+
+    #[cfg(all(feature = "basic-scheme", feature = "digest-scheme"))]
+    #[test]
+    fn rejections() {
+        let builder = crate::PasswordClient::builder()
+            .challenges("Digest realm=\"d\", Basic charset=\"UTF-8\", Bearer")
+            .challenges("Basic realm=\"b\"");
+        let rejections = builder.rejections();
+        let schemes: Vec<&str> = rejections.iter().map(|(s, _)| &s[..]).collect();
+        assert_eq!(schemes, ["Digest", "Basic", "Bearer"]);
+        assert!(rejections[0].1.contains("nonce"), "{:?}", rejections);
+        assert!(rejections[1].1.contains("realm"), "{:?}", rejections);
+        let expected = rejections.to_vec();
+        let (client, rejections) = builder.build_with_rejections();
+        assert_eq!(client.unwrap().scheme(), crate::Scheme::Basic);
+        assert_eq!(rejections, expected);
+
+        // Rejections are also returned on failure.
+        let (client, rejections) = crate::PasswordClient::builder()
+            .challenges("Digest realm=\"d\", Bearer")
+            .build_with_rejections();
+        assert!(client.unwrap_err().contains("nonce"));
+        let schemes: Vec<&str> = rejections.iter().map(|(s, _)| &s[..]).collect();
+        assert_eq!(schemes, ["Digest", "Bearer"]);
+    }
+
+    #[test]
+    fn disabled_scheme() {
+        use crate::PasswordClient;
+        let basic = PasswordClient::try_from("basic realm=\"b\"");
+        #[cfg(feature = "basic-scheme")]
+        basic.unwrap();
+        #[cfg(not(feature = "basic-scheme"))]
+        assert_eq!(
+            basic.unwrap_err(),
+            r#""Basic" scheme requires the "basic-scheme" feature"#
+        );
+
+        let digest = PasswordClient::try_from(r#"Digest realm="d", nonce="n""#);
+        #[cfg(feature = "digest-scheme")]
+        digest.unwrap();
+        #[cfg(not(feature = "digest-scheme"))]
+        assert_eq!(
+            digest.unwrap_err(),
+            r#""Digest" scheme requires the "digest-scheme" feature"#
+        );
+
+        let c = &crate::parse_challenges("Mutual realm=\"m\"").unwrap()[0];
+        assert_eq!(
+            PasswordClient::try_from(c).unwrap_err(),
+            r#"unsupported challenge scheme "Mutual""#
+        );
+    }
+
+    #[cfg(feature = "digest-scheme")]
+    #[test]
+    fn on_challenge() {
+        use std::sync::{Arc, Mutex};
+        type Logged = (String, Option<String>, Option<String>, bool);
+        let log: Arc<Mutex<Vec<Logged>>> = Arc::default();
+        let log2 = log.clone();
+        let input = r#"Digest realm="a", nonce="n", algorithm=MD4, Mutual, "#.to_owned()
+            + r#"Digest realm="b", nonce="n", algorithm=SHA-256, qop="auth, auth-int""#;
+        let challenges = crate::parse_challenges(&input).unwrap();
+        let client = crate::PasswordClient::builder()
+            .on_challenge(move |e| {
+                log2.lock().unwrap().push((
+                    e.scheme.to_owned(),
+                    e.algorithm.map(str::to_owned),
+                    e.qop.map(str::to_owned),
+                    e.rejection.is_some(),
+                ))
+            })
+            .parsed_challenges(&challenges)
+            .build()
+            .unwrap();
+        assert_eq!(client.realm(), Some("b"));
+        let log = log.lock().unwrap();
+        assert_eq!(
+            &log[..],
+            [
+                ("Digest".to_owned(), Some("MD4".to_owned()), None, true),
+                ("Mutual".to_owned(), None, None, true),
+                (
+                    "Digest".to_owned(),
+                    Some("SHA-256".to_owned()),
+                    Some("auth, auth-int".to_owned()),
+                    false
+                ),
+            ]
+        );
+    }
+
+    #[cfg(feature = "digest-scheme")]
+    #[test]
+    fn strongest_digest_algorithm() {
+        use crate::digest::Algorithm;
+        let input = r#"Digest realm="x", nonce="n1", algorithm=MD5, Digest realm="x", nonce="n2", algorithm=SHA-256"#;
+        let client = crate::PasswordClient::try_from(input).unwrap();
+        match client {
+            crate::PasswordClient::Digest(c) => assert_eq!(c.algorithm(), Algorithm::Sha256),
+            #[allow(unreachable_patterns)]
+            _ => panic!("expected Digest, got {:?}", client),
+        }
+
+        let nonce = |c: &crate::PasswordClient| match c {
+            crate::PasswordClient::Digest(c) => c.nonce().to_owned(),
+            #[allow(unreachable_patterns)]
+            _ => unreachable!(),
+        };
+
+        // Among equally strong algorithms, the first is used.
+        let input = r#"Digest realm="x", nonce="n1", algorithm=SHA-512-256, Digest realm="x", nonce="n2", algorithm=SHA-256"#;
+        let client = crate::PasswordClient::try_from(input).unwrap();
+        assert_eq!(nonce(&client), "n1");
+
+        // Considering stops at the first strong algorithm.
+        let input = r#"Digest realm="x", nonce="n1", Digest realm="x", nonce="n2", algorithm=SHA-256, Digest realm="x", nonce="n3""#;
+        let clients = crate::PasswordClient::builder()
+            .challenges(input)
+            .build_all()
+            .unwrap();
+        let nonces: Vec<_> = clients.iter().map(nonce).collect();
+        assert_eq!(nonces, ["n2", "n1"]);
+    }
+
+    #[test]
+    fn challenge_schemes() {
+        let input = r#"Bearer realm="api", NTLM, Basic realm="b", charset="UTF-8", Digest realm="d", nonce="n", algorithm=SHA-256, X-Custom"#;
+        assert_eq!(
+            crate::challenge_schemes(input).unwrap(),
+            ["Bearer", "NTLM", "Basic", "Digest", "X-Custom"],
+        );
+        assert_eq!(
+            crate::challenge_schemes("").unwrap_err().message(),
+            crate::parse_challenges("").unwrap_err().message()
+        );
+        crate::challenge_schemes("Basic, error error").unwrap_err();
+    }
+
+    #[cfg(all(feature = "basic-scheme", feature = "digest-scheme"))]
+    #[test]
+    fn ignore_scheme() {
+        let input = r#"Digest realm="d", nonce="n", Basic realm="b""#;
+        assert_eq!(
+            crate::PasswordClient::try_from(input).unwrap().scheme(),
+            crate::Scheme::Digest
+        );
+        let builder = crate::PasswordClient::builder()
+            .ignore_scheme("DIGEST")
+            .challenges(input);
+        assert_eq!(
+            builder.rejections(),
+            [(
+                "Digest".to_owned(),
+                "scheme \"Digest\" is ignored".to_owned()
+            )]
+        );
+        assert_eq!(builder.build().unwrap().scheme(), crate::Scheme::Basic);
+        assert_eq!(
+            crate::PasswordClient::builder()
+                .ignore_scheme("Basic")
+                .ignore_scheme("Digest")
+                .challenges(input)
+                .build()
+                .unwrap_err(),
+            "scheme \"Digest\" is ignored"
+        );
+    }
+
+    /// Tests that `consider_all` examines challenges after one which can't be
+    /// bettered.
+    #[cfg(all(feature = "basic-scheme", feature = "digest-scheme"))]
+    #[test]
+    fn consider_all() {
+        let input = r#"Digest realm="d", nonce="n", algorithm=SHA-256, Mutual, Basic realm="b""#;
+        let builder = crate::PasswordClient::builder().challenges(input);
+        assert!(builder.rejections().is_empty());
+        assert!(builder.all_challenges().is_empty());
+        assert_eq!(builder.build_all().unwrap().len(), 1);
+
+        let builder = crate::PasswordClient::builder()
+            .consider_all(true)
+            .challenges(input);
+        assert_eq!(builder.rejections().len(), 1);
+        assert_eq!(builder.rejections()[0].0, "Mutual");
+        assert_eq!(
+            builder.all_challenges(),
+            crate::parse_challenges(input)
+                .unwrap()
+                .iter()
+                .map(crate::ChallengeOwned::from)
+                .collect::<Vec<_>>()
+        );
+        let schemes: Vec<_> = builder
+            .build_all()
+            .unwrap()
+            .iter()
+            .map(|c| c.scheme())
+            .collect();
+        assert_eq!(schemes, [crate::Scheme::Digest, crate::Scheme::Basic]);
+        let client = crate::PasswordClient::builder()
+            .consider_all(true)
+            .challenges(input)
+            .build()
+            .unwrap();
+        assert_eq!(client.scheme(), crate::Scheme::Digest);
+    }
+
+    #[cfg(all(feature = "basic-scheme", feature = "digest-scheme"))]
+    #[test]
+    fn extend() {
+        let challenges =
+            crate::parse_challenges(r#"Basic realm="b", Mutual, Digest realm="d", nonce="n""#)
+                .unwrap();
+        let builder: crate::PasswordClientBuilder = challenges.iter().collect();
+        assert_eq!(builder.rejections().len(), 1);
+        assert_eq!(builder.build().unwrap().scheme(), crate::Scheme::Digest);
+
+        // Extending keeps settings made beforehand.
+        let mut builder = crate::PasswordClient::builder().prefer(crate::Scheme::Basic);
+        builder.extend(&challenges[..2]);
+        builder.extend(&challenges[2..]);
+        assert_eq!(builder.build().unwrap().scheme(), crate::Scheme::Basic);
+    }
+
+    #[test]
+    fn empty_quoted_value() {
+        let challenges = crate::parse_challenges(r#"Foo realm="", bar="""#).unwrap();
+        assert_eq!(challenges[0].params.len(), 2);
+        for (_, v) in &challenges[0].params {
+            assert!(v.is_empty());
+            assert_eq!(v.unescaped_len(), 0);
+            assert_eq!(v.to_unescaped(), "");
+            assert!(v.unescaped_starts_with(""));
+            let mut out = String::from("x");
+            v.append_unescaped(&mut out);
+            assert_eq!(out, "x");
+        }
+        assert_eq!(challenges[0].to_string(), r#"Foo realm="", bar="""#);
+    }
+
+    #[test]
+    fn scheme_case() {
+        let input = r#"DIGEST realm="x", nonce="n", basic realm="y""#;
+        let challenges = crate::parse_challenges(input).unwrap();
+        let schemes: Vec<_> = challenges.iter().map(|c| c.scheme).collect();
+        assert_eq!(schemes, ["DIGEST", "basic"]);
+        assert_eq!(challenges[1].to_string(), "basic realm=y");
+        assert_eq!(crate::ChallengeOwned::from(&challenges[0]).scheme, "DIGEST");
+
+        #[cfg(all(feature = "basic-scheme", feature = "digest-scheme"))]
+        {
+            let clients = crate::PasswordClient::builder()
+                .parsed_challenges(&challenges)
+                .build_all()
+                .unwrap();
+            assert_eq!(clients.len(), 2);
+        }
+    }
+
+    #[cfg(feature = "digest-scheme")]
+    #[test]
+    fn strict_digest_params() {
+        let input = r#"Digest realm="r", nonce="n", foo=bar"#;
+        crate::PasswordClient::builder()
+            .challenges(input)
+            .build()
+            .unwrap();
+        let e = crate::PasswordClient::builder()
+            .strict_digest_params(true)
+            .challenges(input)
+            .build()
+            .unwrap_err();
+        assert_eq!(e, "unexpected parameter \"foo\"");
+    }
+
+    #[test]
+    fn password_params_debug() {
+        let p = crate::PasswordParams {
+            username: "Aladdin",
+            password: "open sesame",
+            uri: "/",
+            method: "GET",
+            body: Some(&[]),
+        };
+        let debug = format!("{:?}", &p);
+        assert!(!debug.contains("open sesame"), "{}", debug);
+        assert!(debug.contains("Aladdin"), "{}", debug);
+        assert!(!format!("{:#?}", &p).contains("open sesame"));
+    }
 }
@@ -0,0 +1,196 @@
+// Copyright (C) 2026 Scott Lamb <slamb@slamb.org>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Header plumbing for the multi-round `Negotiate` and `NTLM` schemes, as in
+//! [RFC 4559](https://datatracker.ietf.org/doc/html/rfc4559).
+//!
+//! The tokens themselves come from GSS-API, SSPI, or an NTLM implementation,
+//! which is up to the caller. This module tracks the handshake's progress and
+//! formats and extracts the base64 tokens carried in the headers.
+//!
+//! The continuation challenges carry a `token68` rather than auth-params, so
+//! unlike other challenges, they can't be parsed with
+//! [`crate::parser::ChallengeParser`]. Servers send each as its own header,
+//! which [`NegotiateClient::process_challenge`] takes directly.
+
+use crate::table::is_token68;
+
+/// The progress of a [`NegotiateClient`]'s handshake.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum NegotiateState {
+    /// No token has been sent yet.
+    Initial,
+
+    /// A token has been sent, and the server may reply with another.
+    Continue,
+
+    /// The server has accepted the handshake.
+    Complete,
+}
+
+/// Tracks a `Negotiate` or `NTLM` handshake.
+///
+/// ```rust
+/// use http_auth::negotiate::{NegotiateClient, NegotiateState};
+/// let mut client = NegotiateClient::negotiate();
+///
+/// // The first 401 offers `Negotiate` with no token.
+/// client.process_challenge("Negotiate").unwrap();
+/// let token = "YIIB"; // in practice, from GSS-API.
+/// assert_eq!(client.authorization(token).unwrap(), "Negotiate YIIB");
+///
+/// // The next 401 continues the handshake.
+/// client.process_challenge("Negotiate oYG3").unwrap();
+/// assert_eq!(client.server_token(), Some("oYG3"));
+/// assert_eq!(client.state(), NegotiateState::Continue);
+/// client.authorization("oRswGaAD").unwrap();
+///
+/// // Finally, a 200 may include a token for mutual authentication.
+/// client.complete(Some("Negotiate oRsw")).unwrap();
+/// assert_eq!(client.state(), NegotiateState::Complete);
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NegotiateClient {
+    scheme: &'static str,
+    state: NegotiateState,
+    server_token: Option<Box<str>>,
+}
+
+impl NegotiateClient {
+    /// Creates a client for the `Negotiate` scheme.
+    pub fn negotiate() -> Self {
+        Self::new("Negotiate")
+    }
+
+    /// Creates a client for the `NTLM` scheme.
+    pub fn ntlm() -> Self {
+        Self::new("NTLM")
+    }
+
+    fn new(scheme: &'static str) -> Self {
+        Self {
+            scheme,
+            state: NegotiateState::Initial,
+            server_token: None,
+        }
+    }
+
+    /// Returns the scheme name, `Negotiate` or `NTLM`.
+    #[inline]
+    pub fn scheme(&self) -> &'static str {
+        self.scheme
+    }
+
+    /// Returns the handshake's progress.
+    #[inline]
+    pub fn state(&self) -> NegotiateState {
+        self.state
+    }
+
+    /// Returns the base64 token from the most recent server challenge or
+    /// completion, if any. The caller should decode it and pass it to the
+    /// underlying mechanism.
+    #[inline]
+    pub fn server_token(&self) -> Option<&str> {
+        self.server_token.as_deref()
+    }
+
+    /// Processes a `WWW-Authenticate` or `Proxy-Authenticate` value from a
+    /// `401` or `407` response: either the bare scheme or the scheme followed
+    /// by a token.
+    ///
+    /// Fails if the value is for a different scheme or is malformed, or if
+    /// the server sends a bare scheme after the handshake has started, which
+    /// means it rejected the handshake.
+    pub fn process_challenge(&mut self, value: &str) -> Result<(), String> {
+        if self.state == NegotiateState::Complete {
+            return Err("handshake is already complete".into());
+        }
+        let token = self.parse(value)?;
+        if token.is_none() && self.state != NegotiateState::Initial {
+            return Err(format!("server rejected the {} handshake", self.scheme));
+        }
+        self.server_token = token.map(Into::into);
+        Ok(())
+    }
+
+    /// Returns the `Authorization` or `Proxy-Authorization` value carrying
+    /// the given base64 client token, and advances to
+    /// [`NegotiateState::Continue`].
+    pub fn authorization(&mut self, client_token: &str) -> Result<String, String> {
+        if self.state == NegotiateState::Complete {
+            return Err("handshake is already complete".into());
+        }
+        if !is_token68(client_token) {
+            return Err(format!("invalid token {:?}", client_token));
+        }
+        self.state = NegotiateState::Continue;
+        Ok(format!("{} {}", self.scheme, client_token))
+    }
+
+    /// Marks the handshake complete on a successful response, given its
+    /// `WWW-Authenticate` value, if any. When present, this carries a final
+    /// token for mutual authentication, which the caller should verify.
+    pub fn complete(&mut self, value: Option<&str>) -> Result<(), String> {
+        if self.state != NegotiateState::Continue {
+            return Err("no handshake in progress".into());
+        }
+        self.server_token = match value {
+            Some(v) => self.parse(v)?.map(Into::into),
+            None => None,
+        };
+        self.state = NegotiateState::Complete;
+        Ok(())
+    }
+
+    /// Parses `value` as this scheme, optionally followed by a token.
+    fn parse<'v>(&self, value: &'v str) -> Result<Option<&'v str>, String> {
+        let (scheme, token) = match value.split_once(' ') {
+            Some((s, t)) => (s, Some(t.trim_start_matches(' '))),
+            None => (value, None),
+        };
+        if !scheme.eq_ignore_ascii_case(self.scheme) {
+            return Err(format!(
+                "expected {} challenge, got {:?}",
+                self.scheme, value
+            ));
+        }
+        match token {
+            Some(t) if !is_token68(t) => Err(format!("invalid token in {:?}", value)),
+            t => Ok(t),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NegotiateClient, NegotiateState};
+
+    #[test]
+    fn rejected() {
+        let mut client = NegotiateClient::ntlm();
+        client.process_challenge("NTLM").unwrap();
+        client.authorization("TlRMTVNTUAABAAAAB4IIog==").unwrap();
+        client.process_challenge("ntlm TlRMTVNTUAACAAAA").unwrap();
+        assert_eq!(client.server_token(), Some("TlRMTVNTUAACAAAA"));
+        client.authorization("TlRMTVNTUAADAAAA").unwrap();
+        let e = client.process_challenge("NTLM").unwrap_err();
+        assert!(e.contains("rejected"), "{}", e);
+    }
+
+    #[test]
+    fn malformed() {
+        let mut client = NegotiateClient::negotiate();
+        client.complete(None).unwrap_err();
+        client.process_challenge("Basic realm=\"foo\"").unwrap_err();
+        client.process_challenge("Negotiate a b").unwrap_err();
+        client.process_challenge("Negotiate =").unwrap_err();
+        client.authorization("").unwrap_err();
+        client.authorization("a=b").unwrap_err();
+        assert_eq!(client.state(), NegotiateState::Initial);
+        client.authorization("YIIB").unwrap();
+        client.complete(None).unwrap();
+        assert_eq!(client.server_token(), None);
+        client.authorization("YIIB").unwrap_err();
+    }
+}
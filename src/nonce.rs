@@ -0,0 +1,228 @@
+// Copyright (C) 2026 Scott Lamb <slamb@slamb.org>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Server-side `Digest` nonces, as suggested in [RFC 7616 section
+//! 3.3](https://datatracker.ietf.org/doc/html/rfc7616#section-3.3).
+//!
+//! This is the first piece of server support; verifying responses is not yet
+//! implemented.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use digest::Digest as _;
+
+/// A source of the current time.
+///
+/// Implemented for [`SystemClock`] and for any `Fn() -> SystemTime`, so tests
+/// can control time and environments without a usable system clock can
+/// supply their own.
+pub trait Clock {
+    fn now(&self) -> SystemTime;
+}
+
+/// The system clock, as in [`SystemTime::now`].
+#[derive(Copy, Clone, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    #[inline]
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+impl<F: Fn() -> SystemTime> Clock for F {
+    #[inline]
+    fn now(&self) -> SystemTime {
+        self()
+    }
+}
+
+/// The result of [`NonceGenerator::validate`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum NonceStatus {
+    /// Issued by this generator and not yet expired.
+    Valid,
+
+    /// Issued by this generator but expired. The server should send a new
+    /// challenge with `stale=true`, so the client retries without prompting
+    /// the user.
+    Stale,
+
+    /// Not issued by this generator.
+    Invalid,
+}
+
+/// Generates and validates `Digest` nonces which embed their creation time.
+///
+/// Each nonce is `time-stamp H(time-stamp ":" secret)`, where `time-stamp` is
+/// the creation time as 16 hex digits of seconds since the Unix epoch and `H`
+/// is SHA-256. Thus validation needs no per-nonce server state. It also
+/// doesn't detect replays within the nonce's lifetime; that requires tracking
+/// nonce counts.
+///
+/// The secret should be random, such as 32 bytes from a CSPRNG, and shared by
+/// all servers which may receive the responses.
+///
+/// ```rust
+/// use http_auth::nonce::{NonceGenerator, NonceStatus};
+/// use std::{cell::Cell, time::{Duration, UNIX_EPOCH}};
+/// let now = Cell::new(UNIX_EPOCH + Duration::from_secs(1_000_000));
+/// let generator = NonceGenerator::with_clock(b"secret", || now.get())
+///     .with_lifetime(Duration::from_secs(60));
+/// let nonce = generator.generate();
+/// assert_eq!(generator.validate(&nonce), NonceStatus::Valid);
+/// now.set(now.get() + Duration::from_secs(61));
+/// assert_eq!(generator.validate(&nonce), NonceStatus::Stale);
+/// ```
+pub struct NonceGenerator<C = SystemClock> {
+    secret: Vec<u8>,
+    lifetime: Duration,
+    clock: C,
+}
+
+/// Length of the hex-encoded timestamp prefix.
+const TIMESTAMP_LEN: usize = 16;
+
+impl NonceGenerator {
+    /// Creates a generator using the system clock.
+    pub fn new(secret: &[u8]) -> Self {
+        Self::with_clock(secret, SystemClock)
+    }
+}
+
+impl<C: Clock> NonceGenerator<C> {
+    /// Creates a generator using the given clock.
+    ///
+    /// Nonces expire after five minutes unless changed via
+    /// [`NonceGenerator::with_lifetime`].
+    pub fn with_clock(secret: &[u8], clock: C) -> Self {
+        Self {
+            secret: secret.to_owned(),
+            lifetime: Duration::from_secs(300),
+            clock,
+        }
+    }
+
+    /// Sets how long a nonce remains valid after it's generated.
+    pub fn with_lifetime(mut self, lifetime: Duration) -> Self {
+        self.lifetime = lifetime;
+        self
+    }
+
+    /// Returns a new nonce for a `Digest` challenge.
+    pub fn generate(&self) -> String {
+        let secs = self
+            .clock
+            .now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let mut out = format!("{:016x}", secs);
+        out.push_str(&self.hash(&out));
+        out
+    }
+
+    /// Checks a nonce returned in a client's response.
+    pub fn validate(&self, nonce: &str) -> NonceStatus {
+        if nonce.len() != TIMESTAMP_LEN + 64 || !nonce.is_ascii() {
+            return NonceStatus::Invalid;
+        }
+        let (timestamp, hash) = nonce.split_at(TIMESTAMP_LEN);
+        let secs = match u64::from_str_radix(timestamp, 16) {
+            Ok(s) => s,
+            Err(_) => return NonceStatus::Invalid,
+        };
+
+        // Compare without exiting early, to avoid leaking the expected hash
+        // via timing.
+        let expected = self.hash(timestamp);
+        if expected
+            .bytes()
+            .zip(hash.bytes())
+            .fold(0, |acc, (a, b)| acc | (a ^ b))
+            != 0
+        {
+            return NonceStatus::Invalid;
+        }
+
+        let created = UNIX_EPOCH + Duration::from_secs(secs);
+        match self.clock.now().duration_since(created) {
+            Ok(age) if age > self.lifetime => NonceStatus::Stale,
+
+            // A nonce from the future means the clock has gone backward; it
+            // was still issued by this generator.
+            _ => NonceStatus::Valid,
+        }
+    }
+
+    /// Returns the hex-encoded `H(timestamp ":" secret)`.
+    fn hash(&self, timestamp: &str) -> String {
+        let mut h = sha2::Sha256::new();
+        h.update(timestamp.as_bytes());
+        h.update(b":");
+        h.update(&self.secret);
+        hex::encode(h.finalize())
+    }
+}
+
+impl<C> std::fmt::Debug for NonceGenerator<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NonceGenerator")
+            .field("secret", &"<redacted>")
+            .field("lifetime", &self.lifetime)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NonceGenerator, NonceStatus};
+    use std::{
+        cell::Cell,
+        time::{Duration, UNIX_EPOCH},
+    };
+
+    #[test]
+    fn expiry() {
+        let now = Cell::new(UNIX_EPOCH + Duration::from_secs(1_700_000_000));
+        let generator = NonceGenerator::with_clock(b"secret", || now.get());
+        let nonce = generator.generate();
+        assert!(nonce.starts_with("000000006553f100"), "{}", nonce);
+        assert_eq!(generator.validate(&nonce), NonceStatus::Valid);
+
+        now.set(now.get() + Duration::from_secs(300));
+        assert_eq!(generator.validate(&nonce), NonceStatus::Valid);
+        now.set(now.get() + Duration::from_secs(1));
+        assert_eq!(generator.validate(&nonce), NonceStatus::Stale);
+
+        // A clock that has gone backward doesn't invalidate the nonce.
+        now.set(UNIX_EPOCH);
+        assert_eq!(generator.validate(&nonce), NonceStatus::Valid);
+    }
+
+    #[test]
+    fn invalid() {
+        let generator = NonceGenerator::new(b"secret");
+        let nonce = generator.generate();
+        assert_eq!(generator.validate(&nonce), NonceStatus::Valid);
+        assert_eq!(
+            NonceGenerator::new(b"other").validate(&nonce),
+            NonceStatus::Invalid
+        );
+
+        // Alter the timestamp, as if to extend the lifetime.
+        let mut tampered = nonce.clone();
+        let last_digit = if &nonce[15..16] == "f" { "0" } else { "f" };
+        tampered.replace_range(15..16, last_digit);
+        assert_eq!(generator.validate(&tampered), NonceStatus::Invalid);
+
+        for bad in ["", "x", &nonce[1..], &format!("{}0", nonce)] {
+            assert_eq!(generator.validate(bad), NonceStatus::Invalid, "{:?}", bad);
+        }
+        assert_eq!(
+            generator.validate(&format!("{}é", &nonce[..nonce.len() - 2])),
+            NonceStatus::Invalid
+        );
+    }
+}
@@ -12,7 +12,7 @@
 // grammar, so we verify correctness via a fuzz tester that compares with a
 // nom-based parser. See `fuzz/fuzz_targets/parse_challenges.rs`.
 
-use std::{fmt::Display, ops::Range};
+use std::{fmt::Display, marker::PhantomData, ops::Range};
 
 use crate::{ChallengeRef, ParamValue};
 
@@ -34,10 +34,14 @@ macro_rules! trace {
 /// separate both challenges and parameters, a challenge's end is found after
 /// parsing the *following* challenge's scheme name.) On encountering a syntax
 /// error, it yields `Some(Err(_))` and fuses: all subsequent calls to
-/// [`Iterator::next`] will return `None`.
+/// [`Iterator::next`] will return `None`. (See
+/// [`ChallengeParser::recover_from_errors`] to continue instead.)
 ///
 /// See also the [`crate::parse_challenges`] convenience wrapper.
 ///
+/// The input is usually a `&str` but may also be a `&[u8]`, as with
+/// [`ChallengeParser::new_bytes`]. Both are handled by the same state machine.
+///
 /// ## Example
 ///
 /// ```rust
@@ -83,14 +87,94 @@ macro_rules! trace {
 ///         impossible."
 ///     *   No scheme in the [registry](https://www.iana.org/assignments/http-authschemes/http-authschemes.xhtml)
 ///         uses `token68` challenges as of 2021-10-19.
-pub struct ChallengeParser<'i> {
-    input: &'i str,
+pub struct ChallengeParser<'i, I = &'i str> {
+    input: I,
     pos: usize,
     state: State<'i>,
+
+    /// If true, `input` may be only a prefix of the header value, so reaching
+    /// its end stops iteration rather than completing the final challenge or
+    /// producing an EOF error. Used by [`StreamingParser`].
+    partial: bool,
+
+    /// See [`ChallengeParser::allow_space_separated_params`].
+    space_separated_params: bool,
+
+    /// See [`ChallengeParser::recover_from_errors`].
+    recover_from_errors: bool,
+
+    _input: PhantomData<&'i [u8]>,
+}
+
+/// Input accepted by [`ChallengeParser`]: `&str` or `&[u8]`.
+///
+/// This trait is sealed; it can't be implemented outside this crate.
+pub trait Input<'i>: Copy + sealed::Sealed {
+    #[doc(hidden)]
+    fn as_bytes(self) -> &'i [u8];
+
+    /// Returns the given range, which the parser has checked to be ASCII.
+    #[doc(hidden)]
+    fn slice(self, range: Range<usize>) -> &'i str;
+}
+
+mod sealed {
+    pub trait Sealed {}
+    impl Sealed for &str {}
+    impl Sealed for &[u8] {}
+}
+
+impl<'i> Input<'i> for &'i str {
+    #[inline]
+    fn as_bytes(self) -> &'i [u8] {
+        str::as_bytes(self)
+    }
+
+    #[inline]
+    fn slice(self, range: Range<usize>) -> &'i str {
+        &self[range]
+    }
+}
+
+impl<'i> Input<'i> for &'i [u8] {
+    #[inline]
+    fn as_bytes(self) -> &'i [u8] {
+        self
+    }
+
+    #[inline]
+    fn slice(self, range: Range<usize>) -> &'i str {
+        std::str::from_utf8(&self[range]).expect("parser should only accept ASCII")
+    }
 }
 
 impl<'i> ChallengeParser<'i> {
     pub fn new(input: &'i str) -> Self {
+        Self::with_input(input)
+    }
+}
+
+impl<'i> ChallengeParser<'i, &'i [u8]> {
+    /// Parses a header value which hasn't been checked to be valid UTF-8, such
+    /// as [`http::HeaderValue::as_bytes`].
+    ///
+    /// Non-ASCII bytes are rejected as in the `&str` case, so the returned
+    /// challenges still borrow `&str`s from the input.
+    ///
+    /// ```rust
+    /// use http_auth::{parser::ChallengeParser, ChallengeRef};
+    /// let mut parser = ChallengeParser::new_bytes(b"Basic realm=foo, Digest realm=\xff");
+    /// let c = parser.next().unwrap().unwrap();
+    /// assert_eq!(c.scheme, "Basic");
+    /// parser.next().unwrap().unwrap_err();
+    /// ```
+    pub fn new_bytes(input: &'i [u8]) -> Self {
+        Self::with_input(input)
+    }
+}
+
+impl<'i, I: Input<'i>> ChallengeParser<'i, I> {
+    fn with_input(input: I) -> Self {
         ChallengeParser {
             input,
             pos: 0,
@@ -98,24 +182,266 @@ impl<'i> ChallengeParser<'i> {
                 challenge: None,
                 next: Possibilities(P_SCHEME),
             },
+            partial: false,
+            space_separated_params: false,
+            recover_from_errors: false,
+            _input: PhantomData,
         }
     }
+
+    /// Sets whether to accept auth-params separated by whitespace rather than
+    /// a comma, as in `Digest realm="x" nonce="y"`.
+    ///
+    /// This deviates from the RFC 7235 grammar, but some nonconforming servers
+    /// send such challenges and browsers tolerate them. It's off by default.
+    /// When on, any token following a parameter value and whitespace is
+    /// treated as another parameter's key. A following challenge must still be
+    /// introduced with a comma.
+    ///
+    /// ```rust
+    /// use http_auth::{parser::ChallengeParser, ChallengeRef, ParamValue};
+    /// let input = r#"Digest realm="x" nonce="y", qop=auth"#;
+    /// ChallengeParser::new(input).next().unwrap().unwrap_err();
+    /// let c = ChallengeParser::new(input)
+    ///     .allow_space_separated_params(true)
+    ///     .next()
+    ///     .unwrap()
+    ///     .unwrap();
+    /// assert_eq!(c.params.len(), 3);
+    /// ```
+    pub fn allow_space_separated_params(mut self, allow: bool) -> Self {
+        self.space_separated_params = allow;
+        self
+    }
+
+    /// Sets whether to continue with the next challenge after a syntax error,
+    /// rather than fusing.
+    ///
+    /// When on, each syntax error is yielded in place of the malformed
+    /// challenge, and parsing resumes at the next comma which appears to
+    /// introduce another challenge. This is best-effort, intended for
+    /// diagnostics such as showing why authentication failed. It's off by
+    /// default.
+    ///
+    /// ```rust
+    /// use http_auth::parser::ChallengeParser;
+    /// let input = r#"Basic realm="a", Bad realm=;, Digest realm="b", nonce="n""#;
+    /// let results: Vec<_> = ChallengeParser::new(input)
+    ///     .recover_from_errors(true)
+    ///     .collect();
+    /// assert_eq!(results.len(), 3);
+    /// assert_eq!(results[0].as_ref().unwrap().scheme, "Basic");
+    /// assert_eq!(results[1].as_ref().unwrap_err().pos(), input.find(';').unwrap());
+    /// assert_eq!(results[2].as_ref().unwrap().scheme, "Digest");
+    /// ```
+    pub fn recover_from_errors(mut self, recover: bool) -> Self {
+        self.recover_from_errors = recover;
+        self
+    }
+}
+
+/// Parses an `#auth-param` list, as in the `Authentication-Info` header
+/// described in [RFC 7615 section
+/// 3](https://datatracker.ietf.org/doc/html/rfc7615#section-3).
+#[cfg(feature = "digest-scheme")]
+pub(crate) fn parse_params(input: &str) -> Result<Vec<(&str, ParamValue<'_>)>, Error<'_>> {
+    // Reuse the challenge state machine, starting as if just after a
+    // challenge's scheme and comma.
+    let mut parser = ChallengeParser {
+        input,
+        pos: 0,
+        state: State::PreToken {
+            challenge: Some(ChallengeRef::new("")),
+            next: Possibilities(
+                P_WHITESPACE | P_PARAM_KEY | P_EOF | P_COMMA_PARAM_KEY | P_COMMA_EOF,
+            ),
+        },
+        partial: false,
+        space_separated_params: false,
+        recover_from_errors: false,
+        _input: PhantomData,
+    };
+    let params = match parser.next() {
+        Some(c) => c?.params,
+        None => Vec::new(),
+    };
+    if parser.next().is_some() {
+        // The state machine found what it considered to be a scheme.
+        return Err(Error {
+            input: input.as_bytes(),
+            pos: parser.pos,
+            error: "expected only auth-params",
+            incomplete: false,
+        });
+    }
+    Ok(params)
+}
+
+/// Incrementally parses a challenge list which arrives in pieces, such as a
+/// header value being read off a socket.
+///
+/// Unlike [`ChallengeParser`], this owns a buffer of the input not yet
+/// returned. [`StreamingParser::feed`] returns each challenge as soon as its
+/// end has been found; the next call discards it from the buffer and resumes
+/// parsing at the start of the first challenge not yet returned. Thus the
+/// buffer never holds more than the challenges the latest call returned
+/// (which they borrow), the pending challenge, and the latest piece of input.
+/// Each call's work is proportional to the pending challenge plus the new
+/// piece.
+/// [`StreamingParser::finish`] returns the remainder once the full value has
+/// been supplied. Syntax errors are reported as soon as they're certain, but
+/// errors that could be caused by truncated input are deferred until
+/// `finish`. Error positions are relative to the retained buffer.
+///
+/// ## Example
+///
+/// ```rust
+/// use http_auth::{parser::StreamingParser, ChallengeRef};
+/// let mut parser = StreamingParser::new();
+/// assert_eq!(parser.feed("UnsupportedSchemeA, Basic re").unwrap(), vec![
+///     ChallengeRef::new("UnsupportedSchemeA"),
+/// ]);
+/// assert_eq!(parser.feed("alm=\"foo\"").unwrap(), vec![]);
+/// let c = parser.finish().unwrap();
+/// assert_eq!(c.len(), 1);
+/// assert_eq!(c[0].scheme, "Basic");
+/// ```
+#[derive(Debug, Default)]
+pub struct StreamingParser {
+    /// The input from the last call's pending challenge onward.
+    buf: String,
+
+    /// The byte offset within `buf` of the first challenge not yet returned.
+    /// Everything before it is discarded on the next call.
+    resume: usize,
+
+    /// True after [`StreamingParser::finish`] has returned successfully.
+    finished: bool,
+}
+
+impl StreamingParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends `more` to the input, returning any newly completed challenges.
+    ///
+    /// The returned challenges borrow from this parser's buffer.
+    pub fn feed(&mut self, more: &str) -> Result<Vec<ChallengeRef<'_>>, Error<'_>> {
+        self.discard_returned();
+        self.buf.push_str(more);
+        let mut parser = ChallengeParser::new(&self.buf[..]);
+        parser.partial = true;
+        let mut new = Vec::new();
+        for c in &mut parser {
+            new.push(c?);
+        }
+        if !new.is_empty() {
+            // Returning a challenge means the next one's scheme has been seen.
+            self.resume = parser
+                .pending_start()
+                .expect("parser holds the next challenge after returning one");
+        }
+        Ok(new)
+    }
+
+    /// Treats the input so far as the full header value, returning all
+    /// challenges not yet returned by [`StreamingParser::feed`].
+    pub fn finish(&mut self) -> Result<Vec<ChallengeRef<'_>>, Error<'_>> {
+        if self.finished {
+            return Ok(Vec::new());
+        }
+        self.discard_returned();
+        let new = ChallengeParser::new(&self.buf[..]).collect::<Result<Vec<_>, _>>()?;
+        self.finished = true;
+        Ok(new)
+    }
+
+    /// Drops the challenges returned by the previous call, whose borrows have
+    /// ended, from the buffer.
+    fn discard_returned(&mut self) {
+        self.buf.drain(..self.resume);
+        self.resume = 0;
+    }
 }
 
 /// Describes a parse error and where in the input it occurs.
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+///
+/// The `Display` impl includes the full input with a marker at the error, so
+/// it's suitable for logging as-is:
+///
+/// ```rust
+/// let e = http_auth::parse_challenges("Basic realm=foo; bar").unwrap_err();
+/// assert_eq!(
+///     e.to_string(),
+///     "invalid byte at byte 15: Basic realm=foo(HERE-->); bar",
+/// );
+/// assert_eq!(e.pos(), 15);
+/// assert_eq!(e.message(), "invalid byte");
+/// ```
+#[derive(Copy, Clone, Eq, PartialEq)]
 pub struct Error<'i> {
-    input: &'i str,
+    input: &'i [u8],
     pos: usize,
     error: &'static str,
+    incomplete: bool,
 }
 
 impl<'i> Error<'i> {
-    fn invalid_byte(input: &'i str, pos: usize) -> Self {
+    /// Returns the byte offset within the input at which the error occurs.
+    #[inline]
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+
+    /// Returns a description of the error, without the input context.
+    #[inline]
+    pub fn message(&self) -> &'static str {
+        self.error
+    }
+
+    /// Returns true if the input ended prematurely, such as within a quoted
+    /// string, so appending more input may make it valid.
+    ///
+    /// The one-shot parsing functions treat the input as complete, so this is
+    /// an error like any other for them. A caller reading a header value
+    /// incrementally may instead wait for more data; see also
+    /// [`StreamingParser`], which defers such errors until
+    /// [`StreamingParser::finish`].
+    ///
+    /// ```rust
+    /// let e = http_auth::parse_challenges(r#"Basic realm="fo"#).unwrap_err();
+    /// assert!(e.is_incomplete());
+    /// let e = http_auth::parse_challenges(r#"Basic realm="fo"; x"#).unwrap_err();
+    /// assert!(!e.is_incomplete());
+    /// ```
+    #[inline]
+    pub fn is_incomplete(&self) -> bool {
+        self.incomplete
+    }
+
+    /// Returns an error at the end of the input, which more input could fix.
+    fn eof(input: &'i [u8], error: &'static str) -> Self {
+        Self {
+            input,
+            pos: input.len(),
+            error,
+            incomplete: true,
+        }
+    }
+
+    /// Returns an error for an unexpected byte, distinguishing control
+    /// characters (other than HTAB), which are never valid in a header value.
+    fn invalid_byte(input: &'i [u8], pos: usize) -> Self {
+        let error = match input.get(pos) {
+            Some(&b) if b.is_ascii_control() && b != b'\t' => "control character",
+            _ => "invalid byte",
+        };
         Self {
             input,
             pos,
-            error: "invalid byte",
+            error,
+            incomplete: false,
         }
     }
 }
@@ -129,15 +455,44 @@ impl<'i> Display for Error<'i> {
             self.pos,
             format_args!(
                 "{}(HERE-->){}",
-                &self.input[..self.pos],
-                &self.input[self.pos..]
+                String::from_utf8_lossy(&self.input[..self.pos]),
+                String::from_utf8_lossy(&self.input[self.pos..])
             ),
         )
     }
 }
 
+impl<'i> std::fmt::Debug for Error<'i> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Error")
+            .field("input", &String::from_utf8_lossy(self.input))
+            .field("pos", &self.pos)
+            .field("error", &self.error)
+            .field("incomplete", &self.incomplete)
+            .finish()
+    }
+}
+
 impl<'i> std::error::Error for Error<'i> {}
 
+/// Converts to [`std::io::ErrorKind::InvalidData`], for use with `?` in
+/// functions returning [`std::io::Result`].
+///
+/// As `io::Error` can't borrow the input, this carries the formatted message.
+///
+/// ```rust
+/// fn parse(input: &str) -> std::io::Result<usize> {
+///     Ok(http_auth::parse_challenges(input)?.len())
+/// }
+/// let e = parse("Basic realm=foo; bar").unwrap_err();
+/// assert_eq!(e.kind(), std::io::ErrorKind::InvalidData);
+/// ```
+impl From<Error<'_>> for std::io::Error {
+    fn from(e: Error<'_>) -> Self {
+        std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string())
+    }
+}
+
 /// A set of zero or more `P_*` values indicating possibilities for the current
 /// and/or upcoming tokens.
 #[derive(Copy, Clone, PartialEq, Eq)]
@@ -216,11 +571,36 @@ enum State<'i> {
     },
 }
 
-impl<'i> Iterator for ChallengeParser<'i> {
-    type Item = Result<ChallengeRef<'i>, Error<'i>>;
+impl<'i, I: Input<'i>> ChallengeParser<'i, I> {
+    /// Parses the next challenge, leaving `self.state` as `Done` on error.
+    fn parse_next(&mut self) -> Option<Result<ChallengeRef<'i>, Error<'i>>> {
+        if self.pos == 0 && !self.partial {
+            // Give a clearer error than the state machine would for an empty
+            // or whitespace-only header value.
+            let input = self.input.as_bytes();
+            if let State::PreToken {
+                challenge: None, ..
+            } = self.state
+            {
+                if input.iter().all(|&b| (char_classes(b) & C_OWS) != 0) {
+                    self.state = State::Done;
+                    return Some(Err(Error::eof(input, "no challenge present")));
+                }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        while self.pos < self.input.len() {
+                // Likewise for a leading UTF-8 byte order mark, which some
+                // servers send. Callers can strip it if they wish to accept it.
+                if input.starts_with("\u{feff}".as_bytes()) {
+                    self.state = State::Done;
+                    return Some(Err(Error {
+                        input,
+                        pos: 0,
+                        error: "unexpected byte order mark",
+                        incomplete: false,
+                    }));
+                }
+            }
+        }
+        while self.pos < self.input.as_bytes().len() {
             let b = self.input.as_bytes()[self.pos];
             let classes = char_classes(b);
             match std::mem::replace(&mut self.state, State::Done) {
@@ -233,9 +613,14 @@ impl<'i> Iterator for ChallengeParser<'i> {
                         char::from(b)
                     );
                     if (classes & C_OWS) != 0 && (next.0 & P_WHITESPACE) != 0 {
+                        let mut next = next.0 & !P_EOF;
+                        if self.space_separated_params && (next & P_COMMA_PARAM_KEY) != 0 {
+                            // Following a param value; allow another without a comma.
+                            next |= P_PARAM_KEY;
+                        }
                         self.state = State::PreToken {
                             challenge,
-                            next: Possibilities(next.0 & !P_EOF),
+                            next: Possibilities(next),
                         }
                     } else if b == b',' {
                         let next = Possibilities(
@@ -261,7 +646,7 @@ impl<'i> Iterator for ChallengeParser<'i> {
                             cur: Possibilities(next.0 & (P_SCHEME | P_PARAM_KEY)),
                         }
                     } else {
-                        return Some(Err(Error::invalid_byte(self.input, self.pos)));
+                        return Some(Err(Error::invalid_byte(self.input.as_bytes(), self.pos)));
                     }
                 }
                 State::Token {
@@ -288,12 +673,15 @@ impl<'i> Iterator for ChallengeParser<'i> {
                             // Ending a scheme, starting a parameter key without an intermediate comma.
                             // The whitespace between must be exactly one space.
                             if (cur.0 & P_SCHEME) == 0
-                                || &self.input[token_pos.end..self.pos] != " "
+                                || &self.input.as_bytes()[token_pos.end..self.pos] != b" "
                             {
-                                return Some(Err(Error::invalid_byte(self.input, self.pos)));
+                                return Some(Err(Error::invalid_byte(
+                                    self.input.as_bytes(),
+                                    self.pos,
+                                )));
                             }
                             self.state = State::Token {
-                                challenge: Some(ChallengeRef::new(&self.input[token_pos])),
+                                challenge: Some(ChallengeRef::new(self.input.slice(token_pos))),
                                 token_pos: self.pos..self.pos + 1,
                                 cur: Possibilities(P_PARAM_KEY),
                             };
@@ -306,7 +694,7 @@ impl<'i> Iterator for ChallengeParser<'i> {
                         match b {
                             b',' if (cur.0 & P_SCHEME) != 0 => {
                                 self.state = State::PreToken {
-                                    challenge: Some(ChallengeRef::new(&self.input[token_pos])),
+                                    challenge: Some(ChallengeRef::new(self.input.slice(token_pos))),
                                     next: Possibilities(
                                         P_SCHEME | P_WHITESPACE | P_EOF | P_COMMA_EOF,
                                     ),
@@ -325,9 +713,10 @@ impl<'i> Iterator for ChallengeParser<'i> {
                                 }
                                 None => {
                                     return Some(Err(Error {
-                                        input: self.input,
+                                        input: self.input.as_bytes(),
                                         pos: self.pos,
                                         error: "= without existing challenge",
+                                        incomplete: false,
                                     }));
                                 }
                             },
@@ -340,7 +729,12 @@ impl<'i> Iterator for ChallengeParser<'i> {
                                 }
                             }
 
-                            _ => return Some(Err(Error::invalid_byte(self.input, self.pos))),
+                            _ => {
+                                return Some(Err(Error::invalid_byte(
+                                    self.input.as_bytes(),
+                                    self.pos,
+                                )))
+                            }
                         }
                     }
                 }
@@ -365,7 +759,7 @@ impl<'i> Iterator for ChallengeParser<'i> {
                             value_start: self.pos,
                         };
                     } else {
-                        return Some(Err(Error::invalid_byte(self.input, self.pos)));
+                        return Some(Err(Error::invalid_byte(self.input.as_bytes(), self.pos)));
                     }
                 }
                 State::ParamUnquotedValue {
@@ -382,22 +776,26 @@ impl<'i> Iterator for ChallengeParser<'i> {
                         };
                     } else if (classes & C_OWS) != 0 {
                         challenge.params.push((
-                            &self.input[key_pos],
+                            self.input.slice(key_pos),
                             ParamValue {
                                 escapes: 0,
-                                escaped: &self.input[value_start..self.pos],
+                                escaped: self.input.slice(value_start..self.pos),
                             },
                         ));
+                        let mut next = P_WHITESPACE | P_COMMA_PARAM_KEY | P_COMMA_EOF;
+                        if self.space_separated_params {
+                            next |= P_PARAM_KEY;
+                        }
                         self.state = State::PreToken {
                             challenge: Some(challenge),
-                            next: Possibilities(P_WHITESPACE | P_COMMA_PARAM_KEY | P_COMMA_EOF),
+                            next: Possibilities(next),
                         };
                     } else if b == b',' {
                         challenge.params.push((
-                            &self.input[key_pos],
+                            self.input.slice(key_pos),
                             ParamValue {
                                 escapes: 0,
-                                escaped: &self.input[value_start..self.pos],
+                                escaped: self.input.slice(value_start..self.pos),
                             },
                         ));
                         self.state = State::PreToken {
@@ -412,7 +810,7 @@ impl<'i> Iterator for ChallengeParser<'i> {
                             ),
                         };
                     } else {
-                        return Some(Err(Error::invalid_byte(self.input, self.pos)));
+                        return Some(Err(Error::invalid_byte(self.input.as_bytes(), self.pos)));
                     }
                 }
                 State::ParamQuotedValue {
@@ -425,7 +823,7 @@ impl<'i> Iterator for ChallengeParser<'i> {
                     trace!("ParamQuotedValue pos={} b={:?}", self.pos, char::from(b));
                     if in_backslash {
                         if (classes & C_ESCAPABLE) == 0 {
-                            return Some(Err(Error::invalid_byte(self.input, self.pos)));
+                            return Some(Err(Error::invalid_byte(self.input.as_bytes(), self.pos)));
                         }
                         self.state = State::ParamQuotedValue {
                             challenge,
@@ -444,10 +842,10 @@ impl<'i> Iterator for ChallengeParser<'i> {
                         };
                     } else if b == b'"' {
                         challenge.params.push((
-                            &self.input[key_pos],
+                            self.input.slice(key_pos),
                             ParamValue {
                                 escapes,
-                                escaped: &self.input[value_start..self.pos],
+                                escaped: self.input.slice(value_start..self.pos),
                             },
                         ));
                         self.state = State::PreToken {
@@ -465,12 +863,15 @@ impl<'i> Iterator for ChallengeParser<'i> {
                             in_backslash,
                         };
                     } else {
-                        return Some(Err(Error::invalid_byte(self.input, self.pos)));
+                        return Some(Err(Error::invalid_byte(self.input.as_bytes(), self.pos)));
                     }
                 }
             };
             self.pos += 1;
         }
+        if self.partial {
+            return None;
+        }
         match std::mem::replace(&mut self.state, State::Done) {
             State::Done => {}
             State::PreToken {
@@ -478,11 +879,7 @@ impl<'i> Iterator for ChallengeParser<'i> {
             } => {
                 trace!("eof, PreToken({:?})", next);
                 if (next.0 & P_EOF) == 0 {
-                    return Some(Err(Error {
-                        input: self.input,
-                        pos: self.input.len(),
-                        error: "unexpected EOF",
-                    }));
+                    return Some(Err(Error::eof(self.input.as_bytes(), "unexpected EOF")));
                 }
                 if let Some(challenge) = challenge {
                     return Some(Ok(challenge));
@@ -495,18 +892,18 @@ impl<'i> Iterator for ChallengeParser<'i> {
             } => {
                 trace!("eof, Token({:?})", cur);
                 if (cur.0 & P_SCHEME) == 0 {
-                    return Some(Err(Error {
-                        input: self.input,
-                        pos: self.input.len(),
-                        error: "unexpected EOF expecting =",
-                    }));
+                    return Some(Err(Error::eof(
+                        self.input.as_bytes(),
+                        "unexpected EOF expecting =",
+                    )));
                 }
-                if token_pos.end != self.input.len() && &self.input[token_pos.end..] != " " {
-                    return Some(Err(Error {
-                        input: self.input,
-                        pos: self.input.len(),
-                        error: "EOF after whitespace",
-                    }));
+                if token_pos.end != self.input.as_bytes().len()
+                    && &self.input.as_bytes()[token_pos.end..] != b" "
+                {
+                    return Some(Err(Error::eof(
+                        self.input.as_bytes(),
+                        "EOF after whitespace",
+                    )));
                 }
                 if let Some(challenge) = challenge {
                     self.state = State::Token {
@@ -516,15 +913,14 @@ impl<'i> Iterator for ChallengeParser<'i> {
                     };
                     return Some(Ok(challenge));
                 }
-                return Some(Ok(ChallengeRef::new(&self.input[token_pos])));
+                return Some(Ok(ChallengeRef::new(self.input.slice(token_pos))));
             }
             State::PostEquals { .. } => {
                 trace!("eof, PostEquals");
-                return Some(Err(Error {
-                    input: self.input,
-                    pos: self.input.len(),
-                    error: "unexpected EOF expecting param value",
-                }));
+                return Some(Err(Error::eof(
+                    self.input.as_bytes(),
+                    "unexpected EOF expecting param value",
+                )));
             }
             State::ParamUnquotedValue {
                 mut challenge,
@@ -533,25 +929,144 @@ impl<'i> Iterator for ChallengeParser<'i> {
             } => {
                 trace!("eof, ParamUnquotedValue");
                 challenge.params.push((
-                    &self.input[key_pos],
+                    self.input.slice(key_pos),
                     ParamValue {
                         escapes: 0,
-                        escaped: &self.input[value_start..],
+                        escaped: self.input.slice(value_start..self.input.as_bytes().len()),
                     },
                 ));
                 return Some(Ok(challenge));
             }
             State::ParamQuotedValue { .. } => {
                 trace!("eof, ParamQuotedValue");
-                return Some(Err(Error {
-                    input: self.input,
-                    pos: self.input.len(),
-                    error: "unexpected EOF in quoted param value",
-                }));
+                return Some(Err(Error::eof(
+                    self.input.as_bytes(),
+                    "unexpected EOF in quoted param value",
+                )));
             }
         }
         None
     }
+
+    /// Returns the byte offset of the scheme of the challenge currently being
+    /// parsed, if any.
+    fn pending_start(&self) -> Option<usize> {
+        let challenge = match &self.state {
+            State::Done => None,
+            State::PreToken { challenge, .. } | State::Token { challenge, .. } => {
+                challenge.as_ref()
+            }
+            State::PostEquals { challenge, .. }
+            | State::ParamUnquotedValue { challenge, .. }
+            | State::ParamQuotedValue { challenge, .. } => Some(challenge),
+        }?;
+        Some(challenge.scheme.as_ptr() as usize - self.input.as_bytes().as_ptr() as usize)
+    }
+
+    /// After an error at `from`, positions the parser at the start of the next
+    /// plausible challenge, or leaves it `Done` if there is none.
+    ///
+    /// This is a heuristic: the error itself may have occurred within a quoted
+    /// string, in which case it may resume mid-string.
+    fn recover(&mut self, from: usize) {
+        if let Some(start) = next_scheme_start(self.input.as_bytes(), from) {
+            trace!("recovering at pos={}", start);
+            self.pos = start;
+            self.state = State::PreToken {
+                challenge: None,
+                next: Possibilities(P_SCHEME),
+            };
+        }
+    }
+}
+
+impl<'i> ChallengeParser<'i> {
+    /// Counts the challenges in `input` without parsing them into
+    /// [`ChallengeRef`]s or allocating.
+    ///
+    /// This is a cheap scan for rate-limiting and diagnostics. It counts the
+    /// list elements that begin with a token not followed by `=`, skipping
+    /// commas within quoted strings. It doesn't validate syntax, so on
+    /// malformed input it may disagree with `parse_challenges(input)?.len()`.
+    ///
+    /// ```rust
+    /// use http_auth::parser::ChallengeParser;
+    /// let input = r#"Basic realm="a, b", Digest realm="c", nonce="d", Negotiate"#;
+    /// assert_eq!(ChallengeParser::count_without_parsing(input), 3);
+    /// ```
+    pub fn count_without_parsing(input: &str) -> usize {
+        let input = input.as_bytes();
+        let start = skip_list_separators(input, 0);
+        let mut n = usize::from(is_scheme_at(input, start));
+        let mut from = start;
+        while let Some(start) = next_scheme_start(input, from) {
+            n += 1;
+            from = start;
+        }
+        n
+    }
+}
+
+/// Returns the index of the first byte at or after `i` not in `class`.
+fn skip_class(input: &[u8], mut i: usize, class: u8) -> usize {
+    while i < input.len() && (char_classes(input[i]) & class) != 0 {
+        i += 1;
+    }
+    i
+}
+
+/// Returns the index of the first byte at or after `i` which isn't a comma or
+/// OWS.
+fn skip_list_separators(input: &[u8], mut i: usize) -> usize {
+    while i < input.len() && (input[i] == b',' || (char_classes(input[i]) & C_OWS) != 0) {
+        i += 1;
+    }
+    i
+}
+
+/// Returns true if `start` begins a token which isn't a parameter key.
+fn is_scheme_at(input: &[u8], start: usize) -> bool {
+    let token_end = skip_class(input, start, C_TCHAR);
+    let after = skip_class(input, token_end, C_OWS);
+    token_end > start && input.get(after) != Some(&b'=')
+}
+
+/// Returns the start of the next plausible challenge after `from`: a comma
+/// outside a quoted string followed by a token which isn't a parameter key.
+fn next_scheme_start(input: &[u8], from: usize) -> Option<usize> {
+    let mut i = from;
+    let mut in_quotes = false;
+    while i < input.len() {
+        match input[i] {
+            b'\\' if in_quotes => i += 1,
+            b'"' => in_quotes = !in_quotes,
+            b',' if !in_quotes => {
+                let start = skip_list_separators(input, i + 1);
+                if is_scheme_at(input, start) {
+                    return Some(start);
+                }
+                i = start;
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+impl<'i, I: Input<'i>> Iterator for ChallengeParser<'i, I> {
+    type Item = Result<ChallengeRef<'i>, Error<'i>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let r = self.parse_next();
+        if self.recover_from_errors {
+            if let Some(Err(ref e)) = r {
+                self.recover(e.pos);
+            }
+        }
+        r
+    }
 }
 
 impl std::iter::FusedIterator for ChallengeParser<'_> {}
@@ -587,6 +1102,307 @@ mod tests {
         );
     }
 
+    /// Tests multiple header values joined into one by the caller, including
+    /// with commas inside quoted realms.
+    #[test]
+    fn joined_values() {
+        let a = r#"Basic realm="a, b""#;
+        let b = r#"Digest realm="c,d", nonce="n,", qop="auth,auth-int""#;
+        let c = "Negotiate";
+        for sep in [", ", ",", " ,", " , ", ",,", ", , "] {
+            let joined = [a, b, c].join(sep);
+            let parsed = crate::parse_challenges(&joined).unwrap();
+            let expected: Vec<_> = [a, b, c]
+                .iter()
+                .flat_map(|v| crate::parse_challenges(v).unwrap())
+                .collect();
+            assert_eq!(parsed, expected, "{:?}", joined);
+            assert_eq!(parsed[0].params[0].1.as_escaped(), "a, b");
+        }
+    }
+
+    #[test]
+    fn no_challenge() {
+        for input in ["", "   ", "\t "] {
+            let e = crate::parse_challenges(input).unwrap_err();
+            assert_eq!(e.message(), "no challenge present", "{:?}", input);
+            assert_eq!(e.pos(), input.len());
+        }
+        assert_eq!(
+            crate::parse_challenges(" ,").unwrap_err().message(),
+            "invalid byte"
+        );
+    }
+
+    #[test]
+    fn control_characters() {
+        for (input, pos) in [
+            ("\x01Basic", 0),
+            ("Basic\x01 realm=a", 5),
+            ("Basic realm\x00=a", 11),
+            ("Basic realm=a\x1b", 13),
+            ("Basic realm=\"a\x7f\"", 14),
+            ("Basic realm=\"a\\\n\"", 15),
+            ("Basic realm=a, Digest\r\n realm=b", 21),
+        ] {
+            let e = crate::parse_challenges(input).unwrap_err();
+            assert_eq!(e.message(), "control character", "{:?}", input);
+            assert_eq!(e.pos(), pos, "{:?}", input);
+        }
+
+        // HTAB is allowed within quoted strings and as optional whitespace.
+        let c = crate::parse_challenges("Basic realm=\"a\tb\"\t,\tDigest").unwrap();
+        assert_eq!(c[0].params[0].1.to_unescaped(), "a\tb");
+    }
+
+    /// A `quoted-pair` can't escape CR or LF, so escaping them can't smuggle
+    /// a line break (and a following header) into a param value.
+    #[test]
+    fn escaped_crlf() {
+        for input in [
+            "Basic realm=\"a\\\r\"",
+            "Basic realm=\"a\\\n\"",
+            "Basic realm=\"a\\\r\\\n\"",
+            "Basic realm=\"a\\\r\nSet-Cookie: b\"",
+        ] {
+            let e = crate::parse_challenges(input).unwrap_err();
+            assert_eq!(e.message(), "control character", "{:?}", input);
+            assert_eq!(e.pos(), 15, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn incomplete() {
+        let full = r#"Digest realm="a \"b\"", nonce=n"#;
+        for end in 0..full.len() {
+            if let Err(e) = crate::parse_challenges(&full[..end]) {
+                assert!(e.is_incomplete(), "{:?}", e);
+                assert_eq!(e.pos(), end);
+            }
+        }
+        for input in [
+            r#"Basic realm="foo" "bar""#,
+            "Basic realm=foo; bar",
+            "\u{feff}Basic",
+            "=",
+        ] {
+            let e = crate::parse_challenges(input).unwrap_err();
+            assert!(!e.is_incomplete(), "{:?}", e);
+        }
+        let mut parser = super::StreamingParser::new();
+        assert_eq!(parser.feed(r#"Basic realm="fo"#).unwrap(), vec![]);
+        assert!(parser.finish().unwrap_err().is_incomplete());
+    }
+
+    #[test]
+    fn bytes() {
+        for input in [
+            "",
+            "Basic",
+            r#"Digest realm="a\"b", qop=auth, Basic realm=foo"#,
+            "Basic realm=foo bar",
+            "Basic realm=\"\u{e9}\"",
+        ] {
+            assert_eq!(
+                super::ChallengeParser::new(input).collect::<Vec<_>>(),
+                super::ChallengeParser::new_bytes(input.as_bytes()).collect::<Vec<_>>(),
+                "{:?}",
+                input
+            );
+        }
+        let e = super::ChallengeParser::new_bytes(b"Basic realm=\"\xff\"")
+            .next()
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(e.pos, 13);
+    }
+
+    #[test]
+    fn streaming() {
+        let input = r#"Newauth realm="apps", type=1, title="Login to \"apps\"", Basic realm="simple", Foo, Bar"#;
+        let expected = crate::parse_challenges(input).unwrap();
+
+        // Feed one byte at a time; every challenge should come out exactly once, in order.
+        let mut parser = super::StreamingParser::new();
+        let mut schemes = Vec::new();
+        for i in 0..input.len() {
+            for c in parser.feed(&input[i..i + 1]).unwrap() {
+                schemes.push(c.scheme.to_owned());
+            }
+        }
+        // "Foo" ends only once the following scheme name "Bar" is known to be complete.
+        assert_eq!(schemes, ["Newauth", "Basic"]);
+        assert_eq!(parser.finish().unwrap(), &expected[2..]);
+
+        // Any split point gives the same result.
+        for i in 0..=input.len() {
+            let mut parser = super::StreamingParser::new();
+            let mut all: Vec<_> = parser
+                .feed(&input[..i])
+                .unwrap()
+                .iter()
+                .map(|c| c.to_string())
+                .collect();
+            all.extend(
+                parser
+                    .feed(&input[i..])
+                    .unwrap()
+                    .iter()
+                    .map(|c| c.to_string()),
+            );
+            all.extend(parser.finish().unwrap().iter().map(|c| c.to_string()));
+            let expected: Vec<_> = expected.iter().map(|c| c.to_string()).collect();
+            assert_eq!(all, expected, "split at {}", i);
+        }
+
+        // Later feeds resume after the challenges already returned.
+        let mut parser = super::StreamingParser::new();
+        assert_eq!(parser.feed("A, B x=1, C y").unwrap().len(), 2);
+        assert_eq!(parser.resume, "A, B x=1, ".len());
+        assert_eq!(parser.feed("=\"2\"").unwrap(), vec![]);
+        assert_eq!(parser.buf, "C y=\"2\"");
+        assert_eq!(parser.resume, 0);
+        let c = parser.finish().unwrap();
+        assert_eq!(c.len(), 1);
+        assert_eq!(c[0].scheme, "C");
+        assert_eq!(parser.finish().unwrap(), vec![]);
+
+        // The buffer holds only the latest returned, pending, and new input.
+        let mut parser = super::StreamingParser::new();
+        for i in 0..1000 {
+            let piece = format!("Scheme{} p=\"{}\", ", i, i);
+            let returned = parser.feed(&piece).unwrap();
+            assert_eq!(returned.len(), usize::from(i > 0), "{}", i);
+            assert!(
+                parser.buf.len() <= 2 * piece.len(),
+                "{}: {:?}",
+                i,
+                parser.buf
+            );
+        }
+        let n = parser.feed("End").unwrap().len();
+        assert_eq!(n + parser.finish().unwrap().len(), 2);
+
+        // Errors in the middle are reported immediately; truncation only at finish.
+        let mut parser = super::StreamingParser::new();
+        parser.feed("Basic realm=\"foo").unwrap();
+        parser.finish().unwrap_err();
+        let mut parser = super::StreamingParser::new();
+        parser.feed("Basic realm=\"foo\" error error").unwrap_err();
+    }
+
+    #[test]
+    fn space_separated_params() {
+        let parse = |input| {
+            super::ChallengeParser::new(input)
+                .allow_space_separated_params(true)
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let expected =
+            crate::parse_challenges(r#"Digest realm="x", nonce=y, qop="auth", Basic realm="z""#)
+                .unwrap();
+        assert_eq!(
+            parse(r#"Digest realm="x" nonce=y qop="auth", Basic realm="z""#).unwrap(),
+            expected
+        );
+        assert_eq!(
+            parse(r#"Digest realm="x"  nonce=y, qop="auth", Basic realm="z""#).unwrap(),
+            expected
+        );
+
+        // Conforming input parses the same either way.
+        assert_eq!(
+            parse(r#"Digest realm="x", nonce=y, qop="auth", Basic realm="z""#).unwrap(),
+            expected
+        );
+
+        // A following challenge still needs a comma.
+        parse(r#"Digest realm="x" Basic realm="z""#).unwrap_err();
+
+        // Strict by default.
+        crate::parse_challenges(r#"Digest realm="x" nonce=y"#).unwrap_err();
+    }
+
+    #[test]
+    fn byte_order_mark() {
+        let input = "\u{feff}Basic realm=\"foo\"";
+        let e = crate::parse_challenges(input).unwrap_err();
+        assert_eq!(e.message(), "unexpected byte order mark");
+        assert_eq!(e.pos(), 0);
+        let e = super::ChallengeParser::new_bytes(input.as_bytes())
+            .next()
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(e.message(), "unexpected byte order mark");
+        crate::parse_challenges(input.trim_start_matches('\u{feff}')).unwrap();
+    }
+
+    #[test]
+    fn recover_from_errors() {
+        let parse = |input| {
+            super::ChallengeParser::new(input)
+                .recover_from_errors(true)
+                .map(|r| r.map(|c| c.scheme).map_err(|e| e.message()))
+                .collect::<Vec<_>>()
+        };
+
+        // Commas within quotes and before param keys don't start a challenge.
+        assert_eq!(
+            parse(r#"A x=@, y="1, B", z=2, C"#),
+            vec![Err("invalid byte"), Ok("C")]
+        );
+
+        // Errors at the end.
+        assert_eq!(
+            parse(r#"A, B x="#),
+            vec![Ok("A"), Err("unexpected EOF expecting param value")]
+        );
+        assert_eq!(parse("A x=1;"), vec![Err("invalid byte")]);
+        assert_eq!(parse(" "), vec![Err("no challenge present")]);
+    }
+
+    #[test]
+    fn count_without_parsing() {
+        for input in [
+            r#"Basic realm="a, b""#,
+            r#"Basic realm="a, Digest", charset="UTF-8", Digest realm="x,y", nonce="n""#,
+            r#"Newauth realm="apps", type=1, title="Login to \"apps, Basic\"", Basic realm="simple""#,
+            r#"Basic realm="\\", Negotiate"#,
+            "A, B, ,C",
+            ", A x=1 ,, B",
+            "Negotiate",
+        ] {
+            assert_eq!(
+                super::ChallengeParser::count_without_parsing(input),
+                crate::parse_challenges(input).unwrap().len(),
+                "{}",
+                input
+            );
+        }
+        assert_eq!(super::ChallengeParser::count_without_parsing(""), 0);
+        assert_eq!(super::ChallengeParser::count_without_parsing(" , "), 0);
+    }
+
+    #[cfg(feature = "digest-scheme")]
+    #[test]
+    fn params() {
+        assert_eq!(
+            super::parse_params(r#"nextnonce="foo", qop=auth"#).unwrap(),
+            vec![
+                ("nextnonce", ParamValue::new(0, "foo")),
+                ("qop", ParamValue::new(0, "auth")),
+            ]
+        );
+        assert_eq!(super::parse_params("").unwrap(), vec![]);
+        assert_eq!(
+            super::parse_params(", a=b,").unwrap(),
+            vec![("a", ParamValue::new(0, "b"))]
+        );
+        super::parse_params("a=b, Basic").unwrap_err();
+        super::parse_params("a=b, Basic c=d").unwrap_err();
+        super::parse_params("Basic c=d").unwrap_err();
+    }
+
     #[test]
     fn empty() {
         crate::parse_challenges("").unwrap_err();
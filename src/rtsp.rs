@@ -0,0 +1,160 @@
+// Copyright (C) 2026 Scott Lamb <slamb@slamb.org>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Helpers for [RTSP](https://datatracker.ietf.org/doc/html/rfc2326) clients.
+//!
+//! RTSP borrows HTTP's authentication framework, so [`crate::PasswordClient`]
+//! works unchanged. This module just packages the usual pattern: collect the
+//! `WWW-Authenticate` values of a `401 Unauthorized` response, then respond to
+//! each subsequent request with its method and URI.
+
+use crate::{PasswordClient, PasswordParams};
+
+/// Produces `Authorization` header values for a sequence of RTSP requests.
+///
+/// Holds the credentials and the [`PasswordClient`] built from the server's
+/// challenges. For `Digest`, each [`RtspAuthenticator::authorization`] call
+/// increments the nonce count, so the values should be sent in order.
+///
+/// Requests are assumed to have no body, as with most RTSP methods.
+///
+#[cfg_attr(
+    feature = "basic-scheme",
+    doc = r##"
+```rust
+use http_auth::rtsp::RtspAuthenticator;
+let mut auth = RtspAuthenticator::new(
+    ["Basic realm=\"foo\""],
+    "Aladdin",
+    "open sesame",
+)
+.unwrap();
+assert_eq!(
+    auth.authorization("DESCRIBE", "rtsp://example.com/stream").unwrap(),
+    "Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ==",
+);
+```
+"##
+)]
+pub struct RtspAuthenticator {
+    client: PasswordClient,
+    username: String,
+    password: String,
+}
+
+impl RtspAuthenticator {
+    /// Builds from all `WWW-Authenticate` header values of a response.
+    ///
+    /// See [`crate::PasswordClientBuilder`] for how the challenge is chosen.
+    #[allow(unreachable_code)] // PasswordClient is uninhabited with no schemes.
+    pub fn new<'v, V>(www_authenticate: V, username: &str, password: &str) -> Result<Self, String>
+    where
+        V: IntoIterator<Item = &'v str>,
+    {
+        let client = www_authenticate
+            .into_iter()
+            .fold(PasswordClient::builder(), |b, v| b.challenges(v))
+            .build()?;
+        Ok(Self::from_client(client, username, password))
+    }
+
+    /// Wraps an existing client, such as one built with non-default options.
+    pub fn from_client(client: PasswordClient, username: &str, password: &str) -> Self {
+        Self {
+            client,
+            username: username.to_owned(),
+            password: password.to_owned(),
+        }
+    }
+
+    /// Returns the `Authorization` header value for a request with the given
+    /// method and URI.
+    pub fn authorization(&mut self, method: &str, uri: &str) -> Result<String, String> {
+        self.client.respond(&PasswordParams {
+            username: &self.username,
+            password: &self.password,
+            uri,
+            method,
+            body: None,
+        })
+    }
+
+    /// Returns the underlying client.
+    #[inline]
+    pub fn client(&self) -> &PasswordClient {
+        &self.client
+    }
+
+    /// Returns the underlying client mutably, for example to process an
+    /// `Authentication-Info` header.
+    #[inline]
+    pub fn client_mut(&mut self) -> &mut PasswordClient {
+        &mut self.client
+    }
+}
+
+impl std::fmt::Debug for RtspAuthenticator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RtspAuthenticator")
+            .field("client", &self.client)
+            .field("username", &self.username)
+            .field("password", &"<redacted>")
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RtspAuthenticator;
+
+    #[cfg(feature = "digest-scheme")]
+    #[test]
+    fn digest() {
+        let mut auth = RtspAuthenticator::new(
+            [
+                "Basic realm=\"r\"",
+                "Digest realm=\"r\", nonce=\"n\", qop=\"auth\"",
+            ],
+            "u",
+            "p",
+        )
+        .unwrap();
+        let first = auth.authorization("OPTIONS", "rtsp://h/s").unwrap();
+        let second = auth.authorization("DESCRIBE", "rtsp://h/s").unwrap();
+        assert!(first.starts_with("Digest "), "{}", first);
+        assert!(first.contains("nc=00000001"), "{}", first);
+        assert!(second.contains("nc=00000002"), "{}", second);
+        assert!(second.contains("uri=\"rtsp://h/s\""), "{}", second);
+        assert!(!format!("{:?}", &auth).contains("\"p\""));
+    }
+
+    /// Tests that `H(A2)` uses each RTSP method verbatim.
+    #[cfg(feature = "digest-scheme")]
+    #[test]
+    fn digest_methods() {
+        // Without `qop`, the response is deterministic:
+        // `H(H(u:r:p):n:H(method:rtsp://h/s))`.
+        let mut auth =
+            RtspAuthenticator::new(["Digest realm=\"r\", nonce=\"n\""], "u", "p").unwrap();
+        for (method, response) in [
+            ("DESCRIBE", "607d88858b86ffea4761dc877df0f9a6"),
+            ("SETUP", "029702772c5402281b60a0fc86b39ac9"),
+            ("PLAY", "1d03bd1ba65d2090ef7046a2e4b928f4"),
+            ("GET_PARAMETER", "2facaa584ae5edb9fc4355bb712743d5"),
+            ("describe", "0b8a6746b4ce370694ce881b8d23035e"),
+        ] {
+            let v = auth.authorization(method, "rtsp://h/s").unwrap();
+            assert!(
+                v.contains(&format!("response=\"{}\"", response)),
+                "{}: {}",
+                method,
+                v
+            );
+        }
+    }
+
+    #[test]
+    fn no_challenge() {
+        RtspAuthenticator::new([], "u", "p").unwrap_err();
+    }
+}
@@ -5,6 +5,17 @@
 //! classes the respective bytes are part of. Most classes are referenced from
 //! [RFC 7235 Appendix B: Imported ABNF](https://datatracker.ietf.org/doc/html/rfc7235#appendix-B)
 //! or [RFC 7235 Appendix C: Collected ABNF](https://datatracker.ietf.org/doc/html/rfc7235#appendix-C).
+//!
+//! The per-class predicates are public so that code constructing header
+//! values can validate them against exactly the definitions this crate's parser
+//! uses.
+//!
+//! ```rust
+//! use http_auth::table::{is_qdtext, is_tchar};
+//! assert!("Digest".bytes().all(is_tchar));
+//! assert!(!"a b".bytes().all(is_tchar));
+//! assert!("a b".bytes().all(is_qdtext));
+//! ```
 
 pub(crate) const C_TCHAR: u8 = 1;
 pub(crate) const C_QDTEXT: u8 = 2;
@@ -48,7 +59,7 @@ const fn build_table() -> [u8; 128] {
 
 /// Returns if the byte is a `tchar` as defined in
 /// [RFC 7230 section 3.2.6](https://datatracker.ietf.org/doc/html/rfc7230#section-3.2.6).
-const fn is_tchar(b: u8) -> bool {
+pub const fn is_tchar(b: u8) -> bool {
     // tchar          = "!" / "#" / "$" / "%" / "&" / "'" / "*"
     //                / "+" / "-" / "." / "^" / "_" / "`" / "|" / "~"
     //                / DIGIT / ALPHA
@@ -85,14 +96,22 @@ const fn is_tchar(b: u8) -> bool {
 /// VCHAR          =  %x21-7E
 ///                ; visible (printing) characters
 /// ```
-const fn is_qdtext(b: u8) -> bool {
+pub const fn is_qdtext(b: u8) -> bool {
     matches!(b, b'\t' | b' ' | 0x21 | 0x23..=0x5B | 0x5D..=0x7E)
 }
 
-/// Returns true if the byte is a valid end of a `quoted-pair`, as defined in
+/// Returns true if the byte is a valid end of a `quoted-pair` (excluding
+/// `obs-text`, as with [`is_qdtext`]), as defined in
 /// [RFC 7230 section 3.2.6](https://datatracker.ietf.org/doc/html/rfc7230#section-3.2.6).
-const fn is_escapable(b: u8) -> bool {
-    matches!(b, b'\t' | b' ' | 0x21..=0x7E | 0x80..=0xFF)
+///
+/// ```rust
+/// use http_auth::table::is_escapable;
+/// assert!(is_escapable(b'"'));
+/// assert!(!is_escapable(b'\n'));
+/// assert!(!is_escapable(0xe9));
+/// ```
+pub const fn is_escapable(b: u8) -> bool {
+    matches!(b, b'\t' | b' ' | 0x21..=0x7E)
 }
 
 /// Returns true if the byte is a valid `attr-char` as defined in
@@ -104,7 +123,7 @@ const fn is_escapable(b: u8) -> bool {
 ///                / "^" / "_" / "`" / "|" / "~"
 ///                ; token except ( "*" / "'" / "%" )
 /// ```
-const fn is_attr(b: u8) -> bool {
+pub const fn is_attr(b: u8) -> bool {
     matches!(b,
         b'a'..=b'z'
         | b'A'..=b'Z'
@@ -130,6 +149,49 @@ const fn is_attr(b: u8) -> bool {
 ///      OWS            = *( SP / HTAB )
 ///                     ; optional whitespace
 /// ```
-const fn is_ows(b: u8) -> bool {
+pub const fn is_ows(b: u8) -> bool {
     matches!(b, b' ' | b'\t')
 }
+
+/// Returns true if the string is a valid `token68` as defined in [RFC 7235
+/// section 2.1](https://datatracker.ietf.org/doc/html/rfc7235#section-2.1).
+/// This is the same as the `b64token` of [RFC 6750 section
+/// 2.1](https://datatracker.ietf.org/doc/html/rfc6750#section-2.1).
+///
+/// ```text
+///      token68        = 1*( ALPHA / DIGIT /
+///                           "-" / "." / "_" / "~" / "+" / "/" ) *"="
+/// ```
+///
+/// ```rust
+/// use http_auth::table::is_token68;
+/// assert!(is_token68("QWxhZGRpbjpvcGVuIHNlc2FtZQ=="));
+/// assert!(!is_token68("=="));
+/// assert!(!is_token68("a=b"));
+/// ```
+pub fn is_token68(s: &str) -> bool {
+    let body = s.trim_end_matches('=');
+    !body.is_empty()
+        && body
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"-._~+/".contains(&b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests that the public predicates agree with the parser's table for
+    /// every byte, including non-ASCII ones.
+    #[test]
+    fn predicates_match_table() {
+        for b in 0..=u8::MAX {
+            let classes = char_classes(b);
+            assert_eq!(is_tchar(b), classes & C_TCHAR != 0, "{:#x}", b);
+            assert_eq!(is_qdtext(b), classes & C_QDTEXT != 0, "{:#x}", b);
+            assert_eq!(is_escapable(b), classes & C_ESCAPABLE != 0, "{:#x}", b);
+            assert_eq!(is_ows(b), classes & C_OWS != 0, "{:#x}", b);
+            assert_eq!(is_attr(b), classes & C_ATTR != 0, "{:#x}", b);
+        }
+    }
+}